@@ -2,7 +2,8 @@
 
 use anyhow::Result;
 use arg::{self, Args};
-use std::env;
+use serde::Deserialize;
+use std::{env, fs, path::Path};
 
 // The dock comments above and in this struct are automatically converted to
 // the description when running the program.
@@ -10,14 +11,82 @@ use std::env;
 ///Create a presentation from passed `.ipynb` notebooks.
 #[derive(Args, Debug)]
 pub struct Arguments {
-    ///The path where the presentation will be saved.
-    #[arg(short = "o", long, required)]
+    ///Print the version and exit.
+    #[arg(short = "V", long)]
+    pub version: bool,
+
+    ///Load the options from a `TOML` file, whose keys are the long option names, e.g. `title-class = "center"`. Options that are passed explicitly override the file. `presentation.toml` in the working directory is loaded, if it exists.
+    #[arg(short = "c", long)]
+    pub config: Option<String>,
+
+    ///Turn off a flag that is set in the config by its long name, e.g. `--unset toc`. Can be used multiple times.
+    #[arg(long)]
+    pub unset: Vec<String>,
+
+    ///The path where the presentation will be saved. A directory, e.g. `slides/`, or `--separate` writes one presentation per notebook into it. Use `-` for stdout. Defaults to the notebook with the extension of the format, if a single notebook is converted.
+    #[arg(short = "o", long)]
     pub output: String,
 
-    ///Force override the file if it already exists.
+    ///Force override the file if it already exists, even through a symbolic link.
     #[arg(short = "f", long)]
     pub force: bool,
 
+    ///The format of the presentation: `remark`, `marp`, `reveal` or `beamer`. [default: remark]
+    #[arg(short = "t", long)]
+    pub format: Option<String>,
+
+    ///The title of the presentation, which replaces every `{{title}}` and is shown on the title page.
+    #[arg(long)]
+    pub title: Option<String>,
+
+    ///The author shown on the title page.
+    #[arg(long)]
+    pub author: Option<String>,
+
+    ///The date shown on the title page.
+    #[arg(long)]
+    pub date: Option<String>,
+
+    ///The class of the title page, which is added if a title, author or date is given. [default: center, title]
+    #[arg(long = "title-class")]
+    pub title_class: Option<String>,
+
+    ///Write the content of the file, e.g. a `YAML` front matter with the `theme`, at the top of the presentation. For Marp the keys are merged into the generated front matter.
+    #[arg(long = "front-matter")]
+    pub front_matter: Option<String>,
+
+    ///The line that separates the slides of a remark presentation, e.g. `***` or `<!-- slide -->`. [default: ---]
+    #[arg(long)]
+    pub separator: Option<String>,
+
+    ///Add the text, e.g. the title of the talk, at the top of every slide after the files in front of the first notebook.
+    #[arg(long)]
+    pub header: Option<String>,
+
+    ///Add the text at the bottom of every slide after the files in front of the first notebook. Image paths are relative to the working directory.
+    #[arg(long)]
+    pub footer: Option<String>,
+
+    ///Add the number of the slide and the total number of slides, e.g. `2/10`, at the bottom of every slide after the files in front of the first notebook.
+    #[arg(long = "page-numbers")]
+    pub page_numbers: bool,
+
+    ///Number and count the title page and the table of contents with `--page-numbers` as well.
+    #[arg(long = "number-title-pages")]
+    pub number_title_pages: bool,
+
+    ///Use the first level one heading as title, if no title is given.
+    #[arg(long = "title-from-first-heading")]
+    pub title_from_first_heading: bool,
+
+    ///Add a table of contents page with every level one heading after the title page.
+    #[arg(long)]
+    pub toc: bool,
+
+    ///Set the id of every page without an `id[...]` command to the name of the notebook and the number of the page, e.g. `intro-2`.
+    #[arg(long = "auto-id")]
+    pub auto_id: bool,
+
     ///Enable verbose output.
     #[arg(short = "v", long)]
     pub verbose: bool,
@@ -26,22 +95,606 @@ pub struct Arguments {
     #[arg(short = "d", long)]
     pub debug: bool,
 
-    ///The source paths of the notebooks or folders.
+    ///Write a separate presentation per notebook into the output directory.
+    #[arg(short = "s", long)]
+    pub separate: bool,
+
+    ///Watch the input paths and rebuild the presentation on every change.
+    #[arg(short = "w", long)]
+    pub watch: bool,
+
+    ///Skip the files and directories matching the glob pattern when searching a directory. Can be used multiple times. `.ipynb_checkpoints` is always skipped.
+    #[arg(long)]
+    pub exclude: Vec<String>,
+
+    ///Skip the files and directories ignored by the `.gitignore` files when searching a directory, including the nested files and the files of the parent directories in the repository.
+    #[arg(long = "respect-gitignore")]
+    pub respect_gitignore: bool,
+
+    ///The depth of the subdirectories that are searched for notebooks. `0` only searches the given directories.
+    #[arg(long = "max-depth")]
+    pub max_depth: Option<usize>,
+
+    ///The comma separated extensions of the files that are collected when searching a directory, e.g. `md,ipynb`. Defaults to `ipynb`.
+    #[arg(long = "include-extensions")]
+    pub include_extensions: Option<String>,
+
+    ///Only check the commands of the notebooks and report every error, without writing the presentation.
+    #[arg(long = "dry-run")]
+    pub dry_run: bool,
+
+    ///Print the resolved input paths one per line in the order they are processed and exit, without converting them.
+    #[arg(short = "l", long)]
+    pub list: bool,
+
+    ///Print the number of slides and the words of every slide after the build. Code blocks, command comments and speaker notes are not counted.
+    #[arg(long)]
+    pub stats: bool,
+
+    ///The number of words above which a slide is flagged as dense by `--stats`. [default: 150]
+    #[arg(long = "dense-words")]
+    pub dense_words: Option<usize>,
+
+    ///Stop at the first notebook or file that could not be converted, instead of skipping it. A number of images that differs from the `{}` placeholders of `image[...]` is an error instead of a warning.
+    #[arg(long)]
+    pub strict: bool,
+
+    ///A file listing the notebooks and files of the presentation in order, one path per line relative to the file. Lines starting with `#` are skipped. Directories of the input are not searched with a manifest.
+    #[arg(long)]
+    pub manifest: Option<String>,
+
+    ///Combine the notebooks in reverse order.
+    #[arg(long = "reverse-notebooks")]
+    pub reverse_notebooks: bool,
+
+    ///Write a `json` search index of the presentation to the given path.
+    #[arg(long = "search-index")]
+    pub search_index: Option<String>,
+
+    ///Write a `json` description of every slide with its source notebook, cell index, class and id to the given path.
+    #[arg(long = "manifest-out")]
+    pub manifest_out: Option<String>,
+
+    ///Write a `json` report of all local images that do not exist to the given path.
+    #[arg(long = "broken-images")]
+    pub broken_images: Option<String>,
+
+    ///Resolve the relative image paths against this directory instead of the directory of each notebook.
+    #[arg(long = "relative-to")]
+    pub relative_to: Option<String>,
+
+    ///Copy the local images and linked files into this directory, relative to the output, and point to the copies instead of the originals.
+    #[arg(long)]
+    pub assets: Option<String>,
+
+    ///Join the relative image paths onto this url, e.g. `https://cdn.example.com/week1/`, instead of pointing to the files relative to the output.
+    #[arg(long = "base-url")]
+    pub base_url: Option<String>,
+
+    ///Embed the local images as base64 `data:` urls, so the presentation is a single file.
+    #[arg(long = "embed-images")]
+    pub embed_images: bool,
+
+    ///Only include cells without tags or with one of the given comma separated tags.
+    #[arg(long = "only-tags")]
+    pub only_tags: Option<String>,
+
+    ///The profile of the build, e.g. `instructor`. The content between `if-profile[...]` and `end-if` is only added for the matching profile.
+    #[arg(long)]
+    pub profile: Option<String>,
+
+    ///Keep the pages of `presenter-only` in the presentation, which are dropped for the audience otherwise.
+    #[arg(long)]
+    pub presenter: bool,
+
+    ///Drop the empty pages, e.g. of consecutive `new` commands. Empty pages with a class are kept.
+    #[arg(long = "trim-empty")]
+    pub trim_empty: bool,
+
+    ///Skip cells with one of the given comma separated tags.
+    #[arg(long = "skip-tags")]
+    pub skip_tags: Option<String>,
+
+    ///Add line numbers to the code blocks.
+    #[arg(long = "line-numbers")]
+    pub line_numbers: bool,
+
+    ///Keep the command comments as ordinary HTML comments, e.g. `<!-- new; -->`, on the page they were executed for.
+    #[arg(long = "keep-commands")]
+    pub keep_commands: bool,
+
+    ///The source paths of the notebooks or folders, which may be glob patterns like `week*.ipynb`. Use `-` to read a notebook from stdin.
     pub input: Vec<String>,
 }
 
+impl Arguments {
+    /// Returns the format of the presentation or the [`DEFAULT_FORMAT`].
+    pub fn format(&self) -> &str {
+        self.format.as_deref().unwrap_or(DEFAULT_FORMAT)
+    }
+
+    /// Returns the class of the title page or the [`DEFAULT_TITLE_CLASS`].
+    pub fn title_class(&self) -> &str {
+        self.title_class.as_deref().unwrap_or(DEFAULT_TITLE_CLASS)
+    }
+
+    /// Returns `true` if the presentation can only be written after every
+    /// page is collected, because a title page, table of contents, header,
+    /// footer, page numbers, statistics or a report of the whole deck is
+    /// requested.
+    pub fn needs_whole_deck(&self) -> bool {
+        self.title.is_some()
+            || self.author.is_some()
+            || self.date.is_some()
+            || self.toc
+            || self.title_from_first_heading
+            || self.header.is_some()
+            || self.footer.is_some()
+            || self.page_numbers
+            || self.stats
+            || self.search_index.is_some()
+            || self.manifest_out.is_some()
+            || self.broken_images.is_some()
+    }
+}
+
+/// The format of the presentation, if none is passed or configured.
+pub const DEFAULT_FORMAT: &str = "remark";
+
+/// The class of the title page, if none is passed or configured.
+pub const DEFAULT_TITLE_CLASS: &str = "center, title";
+
+/// The config file that is loaded, if it exists and no `--config` is given.
+pub const DEFAULT_CONFIG: &str = "presentation.toml";
+
+/// The options of a config file. Every key mirrors the long option of
+/// [`Arguments`] with the same name.
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// See [`Arguments::output`].
+    output: Option<String>,
+    /// See [`Arguments::force`].
+    force: Option<bool>,
+    /// See [`Arguments::format`].
+    format: Option<String>,
+    /// See [`Arguments::title`].
+    title: Option<String>,
+    /// See [`Arguments::author`].
+    author: Option<String>,
+    /// See [`Arguments::date`].
+    date: Option<String>,
+    /// See [`Arguments::title_class`].
+    title_class: Option<String>,
+    /// See [`Arguments::front_matter`].
+    front_matter: Option<String>,
+    /// See [`Arguments::separator`].
+    separator: Option<String>,
+    /// See [`Arguments::header`].
+    header: Option<String>,
+    /// See [`Arguments::footer`].
+    footer: Option<String>,
+    /// See [`Arguments::page_numbers`].
+    page_numbers: Option<bool>,
+    /// See [`Arguments::number_title_pages`].
+    number_title_pages: Option<bool>,
+    /// See [`Arguments::title_from_first_heading`].
+    title_from_first_heading: Option<bool>,
+    /// See [`Arguments::toc`].
+    toc: Option<bool>,
+    /// See [`Arguments::auto_id`].
+    auto_id: Option<bool>,
+    /// See [`Arguments::verbose`].
+    verbose: Option<bool>,
+    /// See [`Arguments::debug`].
+    debug: Option<bool>,
+    /// See [`Arguments::separate`].
+    separate: Option<bool>,
+    /// See [`Arguments::watch`].
+    watch: Option<bool>,
+    /// See [`Arguments::exclude`].
+    exclude: Option<Vec<String>>,
+    /// See [`Arguments::respect_gitignore`].
+    respect_gitignore: Option<bool>,
+    /// See [`Arguments::max_depth`].
+    max_depth: Option<usize>,
+    /// See [`Arguments::include_extensions`].
+    include_extensions: Option<String>,
+    /// See [`Arguments::dry_run`].
+    dry_run: Option<bool>,
+    /// See [`Arguments::stats`].
+    stats: Option<bool>,
+    /// See [`Arguments::dense_words`].
+    dense_words: Option<usize>,
+    /// See [`Arguments::strict`].
+    strict: Option<bool>,
+    /// See [`Arguments::manifest`].
+    manifest: Option<String>,
+    /// See [`Arguments::reverse_notebooks`].
+    reverse_notebooks: Option<bool>,
+    /// See [`Arguments::search_index`].
+    search_index: Option<String>,
+    /// See [`Arguments::manifest_out`].
+    manifest_out: Option<String>,
+    /// See [`Arguments::broken_images`].
+    broken_images: Option<String>,
+    /// See [`Arguments::relative_to`].
+    relative_to: Option<String>,
+    /// See [`Arguments::assets`].
+    assets: Option<String>,
+    /// See [`Arguments::base_url`].
+    base_url: Option<String>,
+    /// See [`Arguments::embed_images`].
+    embed_images: Option<bool>,
+    /// See [`Arguments::only_tags`].
+    only_tags: Option<String>,
+    /// See [`Arguments::profile`].
+    profile: Option<String>,
+    /// See [`Arguments::presenter`].
+    presenter: Option<bool>,
+    /// See [`Arguments::trim_empty`].
+    trim_empty: Option<bool>,
+    /// See [`Arguments::skip_tags`].
+    skip_tags: Option<String>,
+    /// See [`Arguments::line_numbers`].
+    line_numbers: Option<bool>,
+    /// See [`Arguments::keep_commands`].
+    keep_commands: Option<bool>,
+    /// See [`Arguments::input`].
+    input: Option<Vec<String>>,
+}
+
+impl Config {
+    /// Loads the config file at the `path`, or the `default` config, e.g.
+    /// the [`DEFAULT_CONFIG`], if no path is given. Returns `None` if no path
+    /// is given and the default config does not exist.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file could not be read or
+    /// parsed, e.g. because of an unknown key.
+    pub fn load(path: Option<&str>, default: &Path) -> Result<Option<Config>> {
+        let path = match path {
+            Some(path) => Path::new(path),
+            None if default.is_file() => default,
+            None => return Ok(None),
+        };
+        let text = fs::read_to_string(path).map_err(|err| {
+            anyhow::Error::msg(format!("Unable to read the config {path:?}. {err}"))
+        })?;
+        let config = toml::from_str(&text).map_err(|err| {
+            anyhow::Error::msg(format!("Unable to parse the config {path:?}. {err}"))
+        })?;
+        Ok(Some(config))
+    }
+
+    /// Turns off the flag with the long name `flag`, so it is not set by
+    /// this config, see [`Arguments::unset`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the `flag` is not the long name
+    /// of a flag of the config.
+    fn unset(&mut self, flag: &str) -> Result<()> {
+        let value = match flag {
+            "force" => &mut self.force,
+            "page-numbers" => &mut self.page_numbers,
+            "number-title-pages" => &mut self.number_title_pages,
+            "title-from-first-heading" => &mut self.title_from_first_heading,
+            "toc" => &mut self.toc,
+            "auto-id" => &mut self.auto_id,
+            "verbose" => &mut self.verbose,
+            "debug" => &mut self.debug,
+            "separate" => &mut self.separate,
+            "watch" => &mut self.watch,
+            "respect-gitignore" => &mut self.respect_gitignore,
+            "dry-run" => &mut self.dry_run,
+            "stats" => &mut self.stats,
+            "strict" => &mut self.strict,
+            "reverse-notebooks" => &mut self.reverse_notebooks,
+            "embed-images" => &mut self.embed_images,
+            "presenter" => &mut self.presenter,
+            "trim-empty" => &mut self.trim_empty,
+            "line-numbers" => &mut self.line_numbers,
+            "keep-commands" => &mut self.keep_commands,
+            _ => {
+                return Err(anyhow::Error::msg(format!(
+                    "Unable to unset `{flag}`, it is not a flag of the config."
+                )))
+            }
+        };
+        *value = None;
+        Ok(())
+    }
+}
+
+/// Fills the options of the `arguments` that were not passed explicitly with
+/// the values of the `config`. A flag is set if it is passed or set in the
+/// config, unless it is turned off with [`Arguments::unset`].
+///
+/// # Errors
+///
+/// This function will return an error if a flag of [`Arguments::unset`] is
+/// not a flag of the config.
+fn merge(mut arguments: Arguments, mut config: Config) -> Result<Arguments> {
+    for flag in &arguments.unset {
+        config.unset(flag)?;
+    }
+
+    if arguments.output.is_empty() {
+        arguments.output = config.output.unwrap_or_default();
+    }
+    if arguments.exclude.is_empty() {
+        arguments.exclude = config.exclude.unwrap_or_default();
+    }
+    if arguments.input.is_empty() {
+        arguments.input = config.input.unwrap_or_default();
+    }
+
+    arguments.format = arguments.format.or(config.format);
+    arguments.title_class = arguments.title_class.or(config.title_class);
+    arguments.title = arguments.title.or(config.title);
+    arguments.author = arguments.author.or(config.author);
+    arguments.date = arguments.date.or(config.date);
+    arguments.front_matter = arguments.front_matter.or(config.front_matter);
+    arguments.separator = arguments.separator.or(config.separator);
+    arguments.header = arguments.header.or(config.header);
+    arguments.footer = arguments.footer.or(config.footer);
+    arguments.max_depth = arguments.max_depth.or(config.max_depth);
+    arguments.include_extensions = arguments.include_extensions.or(config.include_extensions);
+    arguments.dense_words = arguments.dense_words.or(config.dense_words);
+    arguments.manifest = arguments.manifest.or(config.manifest);
+    arguments.search_index = arguments.search_index.or(config.search_index);
+    arguments.manifest_out = arguments.manifest_out.or(config.manifest_out);
+    arguments.broken_images = arguments.broken_images.or(config.broken_images);
+    arguments.relative_to = arguments.relative_to.or(config.relative_to);
+    arguments.assets = arguments.assets.or(config.assets);
+    arguments.base_url = arguments.base_url.or(config.base_url);
+    arguments.only_tags = arguments.only_tags.or(config.only_tags);
+    arguments.profile = arguments.profile.or(config.profile);
+    arguments.skip_tags = arguments.skip_tags.or(config.skip_tags);
+
+    arguments.force |= config.force.unwrap_or_default();
+    arguments.page_numbers |= config.page_numbers.unwrap_or_default();
+    arguments.number_title_pages |= config.number_title_pages.unwrap_or_default();
+    arguments.title_from_first_heading |= config.title_from_first_heading.unwrap_or_default();
+    arguments.toc |= config.toc.unwrap_or_default();
+    arguments.auto_id |= config.auto_id.unwrap_or_default();
+    arguments.verbose |= config.verbose.unwrap_or_default();
+    arguments.debug |= config.debug.unwrap_or_default();
+    arguments.separate |= config.separate.unwrap_or_default();
+    arguments.watch |= config.watch.unwrap_or_default();
+    arguments.respect_gitignore |= config.respect_gitignore.unwrap_or_default();
+    arguments.dry_run |= config.dry_run.unwrap_or_default();
+    arguments.stats |= config.stats.unwrap_or_default();
+    arguments.strict |= config.strict.unwrap_or_default();
+    arguments.reverse_notebooks |= config.reverse_notebooks.unwrap_or_default();
+    arguments.embed_images |= config.embed_images.unwrap_or_default();
+    arguments.line_numbers |= config.line_numbers.unwrap_or_default();
+    arguments.keep_commands |= config.keep_commands.unwrap_or_default();
+    arguments.presenter |= config.presenter.unwrap_or_default();
+    arguments.trim_empty |= config.trim_empty.unwrap_or_default();
+    Ok(arguments)
+}
+
+/// Splits a comma separated `list` of an argument into its trimmed, non-empty
+/// items.
+pub fn split_list(list: Option<&str>) -> Vec<String> {
+    list.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 /// Get all passed in arguments.
 ///
 /// # Errors
 ///
-/// This function will return an error if help is requested or a an argument
-/// that is not supported was passed.
+/// This function will return an error if help is requested, a an argument
+/// that is not supported was passed, the config could not be loaded or no
+/// output is given.
 pub fn get_arguments() -> Result<Arguments> {
-    let args = env::args().collect::<Vec<String>>().join(" ");
-    let args = Arguments::from_text(&args);
+    // The first argument is the program itself, which is not an input path.
+    parse_arguments(
+        &env::args().skip(1).collect::<Vec<String>>(),
+        Path::new(DEFAULT_CONFIG),
+    )
+}
+
+/// Parses the `args` and merges them with the config, which is the
+/// `default_config` unless `--config` is passed. The output is not required,
+/// if only the version or the list of the input paths is requested.
+///
+/// # Errors
+///
+/// See [`get_arguments`].
+fn parse_arguments(args: &[String], default_config: &Path) -> Result<Arguments> {
+    let arguments = match Arguments::from_text(&args.join(" ")) {
+        Ok(arguments) => arguments,
+        Err(err) => return Err(anyhow::Error::msg(err.to_string())),
+    };
+
+    let config = Config::load(arguments.config.as_deref(), default_config)?;
+    let arguments = merge(arguments, config.unwrap_or_default())?;
+    if arguments.output.is_empty()
+        && !arguments.version
+        && !arguments.list
+        && single_notebook(&arguments.input).is_none()
+    {
+        return Err(anyhow::Error::msg(
+            "The output path is missing. Pass it with `--output` or in the config, unless a single notebook is converted.",
+        ));
+    }
+    Ok(arguments)
+}
+
+/// Returns the path of the notebook, if the `input` is a single `.ipynb`
+/// notebook and not a glob pattern.
+fn single_notebook(input: &[String]) -> Option<&Path> {
+    let [input] = input else {
+        return None;
+    };
+    let path = Path::new(input);
+    (path.extension().is_some_and(|ext| ext == "ipynb") && !input.contains(['*', '?', '[']))
+        .then_some(path)
+}
+
+/// Returns the output path for a single notebook of the `input`, which is the
+/// notebook with the `extension` of the format in the same directory.
+pub fn default_output(input: &[String], extension: &str) -> Option<String> {
+    single_notebook(input)?
+        .with_extension(extension)
+        .to_str()
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use arg::Args;
+
+    use super::{default_output, merge, parse_arguments, Arguments, Config, DEFAULT_CONFIG};
+
+    /// Parses the `args` without a config, even if the working directory has
+    /// a [`DEFAULT_CONFIG`].
+    fn parse(args: &[&str]) -> anyhow::Result<Arguments> {
+        let dir = tempfile::tempdir().unwrap();
+        let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        parse_arguments(&args, &dir.path().join(DEFAULT_CONFIG))
+    }
+
+    #[test]
+    fn test_version() {
+        let arguments = parse(&["--version"]).unwrap();
+        assert!(arguments.version);
+        assert!(arguments.output.is_empty());
+        let arguments = parse(&["-V"]).unwrap();
+        assert!(arguments.version);
+        let arguments = parse(&["-l", "notebooks"]).unwrap();
+        assert!(arguments.list);
+
+        let err = parse(&["first.ipynb", "second.ipynb"]).unwrap_err();
+        assert!(err.to_string().contains("The output path is missing."));
+    }
+
+    #[test]
+    fn test_default_output() {
+        let input = vec!["notebooks/week1.ipynb".to_string()];
+        let arguments = parse(&["notebooks/week1.ipynb"]).unwrap();
+        assert!(arguments.output.is_empty());
+        assert_eq!(arguments.format(), "remark");
+        assert_eq!(arguments.title_class(), "center, title");
+        assert_eq!(
+            default_output(&input, "rmd").as_deref(),
+            Some("notebooks/week1.rmd")
+        );
+        assert_eq!(default_output(&["week*.ipynb".to_string()], "rmd"), None);
+        assert_eq!(default_output(&["notebooks".to_string()], "rmd"), None);
+        assert_eq!(
+            default_output(&["a.ipynb".to_string(), "b.ipynb".to_string()], "rmd"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_needs_whole_deck() {
+        assert!(!parse(&["week.ipynb"]).unwrap().needs_whole_deck());
+        assert!(!parse(&["--separator", "***", "week.ipynb"])
+            .unwrap()
+            .needs_whole_deck());
+        for option in [&["--toc"][..], &["--author", "Me"], &["--stats"]] {
+            let args = [option, &["week.ipynb"]].concat();
+            assert!(parse(&args).unwrap().needs_whole_deck(), "{option:?}");
+        }
+    }
+
+    #[test]
+    fn test_merge() {
+        let config: Config = toml::from_str(
+            r#"
+            output = "config.rmd"
+            format = "marp"
+            title-class = "center"
+            title = "Config"
+            author = "Config"
+            exclude = ["drafts"]
+            toc = true
+            input = ["notebooks"]
+            "#,
+        )
+        .unwrap();
+        let text = "--format remark --title Explicit week.ipynb";
+        let arguments = merge(Arguments::from_text(text).unwrap(), config).unwrap();
+
+        assert_eq!(arguments.output, "config.rmd");
+        assert_eq!(arguments.format(), "remark");
+        assert_eq!(arguments.title_class(), "center");
+        assert_eq!(arguments.title.as_deref(), Some("Explicit"));
+        assert_eq!(arguments.author.as_deref(), Some("Config"));
+        assert_eq!(arguments.exclude, vec!["drafts".to_string()]);
+        assert!(arguments.toc);
+        assert_eq!(arguments.input, vec!["week.ipynb".to_string()]);
+    }
+
+    #[test]
+    fn test_merge_stdin() {
+        let config: Config = toml::from_str(
+            r#"
+            format = "marp"
+            title-class = "center"
+            "#,
+        )
+        .unwrap();
+        let arguments = merge(Arguments::from_text("-").unwrap(), config).unwrap();
+
+        assert_eq!(arguments.format(), "marp");
+        assert_eq!(arguments.title_class(), "center");
+        assert_eq!(arguments.input, vec!["-".to_string()]);
+    }
+
+    #[test]
+    fn test_merge_unset() {
+        let config = || -> Config {
+            toml::from_str(
+                r#"
+                toc = true
+                force = true
+                stats = true
+                "#,
+            )
+            .unwrap()
+        };
+        let text = "--unset toc --unset force --force week.ipynb";
+        let arguments = merge(Arguments::from_text(text).unwrap(), config()).unwrap();
+        assert!(!arguments.toc);
+        assert!(arguments.force);
+        assert!(arguments.stats);
+
+        let text = "--unset title week.ipynb";
+        let err = merge(Arguments::from_text(text).unwrap(), config()).unwrap_err();
+        assert!(err.to_string().contains("`title`"));
+    }
+
+    #[test]
+    fn test_load_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("presentation.toml");
+        std::fs::write(&path, "unknown = true").unwrap();
+        assert!(Config::load(path.to_str(), Path::new(DEFAULT_CONFIG)).is_err());
+
+        let missing = dir.path().join("missing.toml");
+        assert!(Config::load(missing.to_str(), Path::new(DEFAULT_CONFIG)).is_err());
+        assert!(Config::load(None, &missing).unwrap().is_none());
+
+        std::fs::write(&path, "format = \"marp\"\nmax-depth = 2").unwrap();
+        let config = Config::load(None, &path).unwrap().unwrap();
+        assert_eq!(config.format.as_deref(), Some("marp"));
+        assert_eq!(config.max_depth, Some(2));
 
-    match args {
-        Ok(args) => Ok(args),
-        Err(err) => Err(anyhow::Error::msg(err.to_string())),
+        let args = vec!["week.ipynb".to_string()];
+        assert_eq!(parse_arguments(&args, &path).unwrap().format(), "marp");
     }
 }
@@ -0,0 +1,123 @@
+//! Finds the local images of the generated presentation that do not exist and
+//! writes them as report. The report is a `json` array, where every entry
+//! holds the `notebook` (or file) the image is used in, the number of the
+//! `slide` starting at `1` and the `path` of the image in the presentation.
+use anyhow::Result;
+use serde::Serialize;
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use crate::path::{decode_path, find_relative_paths, split_suffix};
+
+/// A reference to an image that does not exist.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct BrokenImage {
+    /// The path of the notebook or file the image is used in.
+    pub notebook: PathBuf,
+    /// The number of the slide, starting at `1`.
+    pub slide: usize,
+    /// The path of the image, relative to the presentation.
+    pub path: String,
+}
+
+/// Finds all relative image paths in the `pages` that do not point to an
+/// existing file. The paths are resolved against the directory of the
+/// `output_path` and every page belongs to the notebook or file at the same
+/// index in `paths`. The slides of the pages are separated by the
+/// `separator`.
+pub fn find_broken_images(
+    output_path: &Path,
+    paths: &[PathBuf],
+    pages: &[String],
+    separator: &str,
+) -> Vec<BrokenImage> {
+    let directory = output_path.parent().unwrap_or(Path::new(""));
+    let mut broken_images = vec![];
+    let mut slide = 0;
+    let separator = format!("\n{separator}\n");
+
+    for (notebook, page) in paths.iter().zip(pages) {
+        if page.is_empty() {
+            continue;
+        }
+        for content in page.split(&separator) {
+            slide += 1;
+            for (path, _) in find_relative_paths(content) {
+                if !directory.join(decode_path(split_suffix(&path).0)).is_file() {
+                    broken_images.push(BrokenImage {
+                        notebook: notebook.clone(),
+                        slide,
+                        path,
+                    });
+                }
+            }
+        }
+    }
+
+    broken_images
+}
+
+/// Finds the images of the `pages` that do not exist and writes them as
+/// `json` to the `report_path`.
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be created or
+/// the report could not be written to it.
+pub fn write_broken_images(
+    report_path: PathBuf,
+    output_path: &Path,
+    paths: &[PathBuf],
+    pages: &[String],
+    separator: &str,
+) -> Result<()> {
+    let file = File::create(report_path)?;
+    serde_json::to_writer_pretty(
+        file,
+        &find_broken_images(output_path, paths, pages, separator),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::{find_broken_images, BrokenImage};
+
+    #[test]
+    fn test_find_broken_images() {
+        let paths = vec![
+            PathBuf::from("notebooks/first.ipynb"),
+            PathBuf::from("notebooks/second.ipynb"),
+        ];
+        let pages = vec![
+            "![](../images/image1.png)\n***\n\n![](../images/missing.png)".to_string(),
+            "<img src=\"../images/other.png\">\n![](https://webimage/image.png)".to_string(),
+        ];
+
+        let broken_images = find_broken_images(
+            Path::new("tests/presentations/output.rmd"),
+            &paths,
+            &pages,
+            "***",
+        );
+        assert_eq!(
+            broken_images,
+            vec![
+                BrokenImage {
+                    notebook: paths[0].clone(),
+                    slide: 2,
+                    path: "../images/missing.png".to_string(),
+                },
+                BrokenImage {
+                    notebook: paths[1].clone(),
+                    slide: 3,
+                    path: "../images/other.png".to_string(),
+                },
+            ]
+        );
+    }
+}
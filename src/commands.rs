@@ -4,6 +4,7 @@
 use std::{
     error::Error,
     fmt::{Debug, Display},
+    ops::Range,
     result::Result,
 };
 
@@ -20,11 +21,66 @@ pub enum Command {
     StopAddToPage,
     /// Add the content of the injection to the latest page.
     InjectToPage(String),
+    /// Add the content of the injection to the page at the given offset
+    /// relative to the latest page, e.g. `-1` for the previous page.
+    InjectAtPage(isize, String),
     /// Wrapp the images of a markdown cell in the given string. A more
     /// detailed description can be found in the `readme.md`.
     WrapImage(String),
     /// Set the class of the latest page.
     PageClass(String),
+    /// Drop the class of [`Command::PageClass`], that is not set yet.
+    ClearClass,
+    /// Set the class of the latest page and every following page. An empty
+    /// class clears the sticky class.
+    StickyPageClass(String),
+    /// Set the id of the latest page, which is used to link to the page.
+    PageId(String),
+    /// Split the following added content into a left and a right column at
+    /// the given delimiter.
+    Columns(String),
+    /// Add the content of the file at the given path, relative to the
+    /// notebook, to the latest page.
+    Include(String),
+    /// Set the variable with the given name to the given value.
+    SetVariable(String, String),
+    /// Reveal the top-level list items and paragraphs of the following added
+    /// content one at a time.
+    Fragment,
+    /// Add the given keywords to the tags of the latest page.
+    Keywords(Vec<String>),
+    /// Add the source of a code cell as fenced code block to the latest page.
+    Code,
+    /// Add the stream outputs of a code cell as fenced block to the latest
+    /// page.
+    AddStreamToPage,
+    /// Add the error outputs of a code cell as fenced block to the latest
+    /// page.
+    AddErrorToPage,
+    /// Add the image outputs of a code cell as embedded markdown images to
+    /// the latest page.
+    AddImageToPage,
+    /// Add the source of a raw cell as it is to the latest page.
+    Raw,
+    /// Inject the content in front of the existing content of the latest
+    /// page.
+    InjectTop(String),
+    /// Start a new page at every `## ` heading of the following added
+    /// content.
+    AutoSplit,
+    /// Only execute the following commands and add the following content, if
+    /// the given profile is active, until [`Command::EndIf`].
+    IfProfile(String),
+    /// End the content of a [`Command::IfProfile`].
+    EndIf,
+    /// Only keep the latest page in the presentation for the presenter.
+    PresenterOnly,
+    /// Add the raw HTML to the latest page. Unlike [`Command::InjectToPage`]
+    /// the variables are not substituted.
+    Html(String),
+    /// Trim the leading and trailing whitespace of the content that the cell
+    /// adds to the latest page.
+    Trim,
 }
 
 /// Represents an error encountered during command comment parsing.
@@ -34,10 +90,12 @@ pub enum ParseError {
     UnknownCommand(String),
     /// Indicates an badly formatted contend was used. Contains the corresponding command.
     Content(String),
-    /// Indicates a comma is missing after a command. Contains the remaining string.
-    MissingComma(String),
-    /// Indicates the stream was not fully parsed. Contains the remaining string.
-    Remaining(String),
+    /// Indicates a comma is missing after a command. Contains the remaining string and the
+    /// char offset of it in the stream.
+    MissingComma(String, usize),
+    /// Indicates the stream was not fully parsed. Contains the remaining string and the char
+    /// offset of it in the stream.
+    Remaining(String, usize),
     /// Indicates another undefined parsing error occurred. Contains a vector of `Simple<char>` instances.
     Other(Vec<Simple<char>>),
 }
@@ -45,12 +103,19 @@ pub enum ParseError {
 impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ParseError::UnknownCommand(err) => write!(f, "Unknown command '{err}'. "),
+            ParseError::UnknownCommand(err) => match closest_keyword(err) {
+                Some(keyword) => write!(f, "Unknown command '{err}'. Did you mean '{keyword}'? "),
+                None => write!(f, "Unknown command '{err}'. "),
+            },
             ParseError::Content(err) => {
                 write!(f, "Content after '{err}' could not be parsed correctly. ")
             }
-            ParseError::MissingComma(err) => write!(f, "Missing comma before '{err}'. "),
-            ParseError::Remaining(err) => write!(f, "Unable to parse remaining '{err}'. "),
+            ParseError::MissingComma(err, offset) => {
+                write!(f, "Missing comma before '{err}' at offset {offset}. ")
+            }
+            ParseError::Remaining(err, offset) => {
+                write!(f, "Unable to parse remaining '{err}' at offset {offset}. ")
+            }
             ParseError::Other(err) => write!(f, "Unable to parse '{err:?}'. "),
         }
     }
@@ -58,6 +123,18 @@ impl Display for ParseError {
 
 impl Error for ParseError {}
 
+impl ParseError {
+    /// Returns the char offset of the error in the parsed stream, if it is
+    /// known.
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            ParseError::MissingComma(_, offset) | ParseError::Remaining(_, offset) => Some(*offset),
+            ParseError::Other(err) => err.first().map(|err| err.span().start),
+            ParseError::UnknownCommand(_) | ParseError::Content(_) => None,
+        }
+    }
+}
+
 impl Command {
     /// The char sequence for the `new page` command
     pub const NEW_PAGE: &'static str = "new";
@@ -67,28 +144,171 @@ impl Command {
     pub const STOP_ADD_TO_PAGE: &'static str = "stop-add";
     /// The char sequence for the `inject to page` command
     pub const INJECT_TP_PAGE: &'static str = "inject";
+    /// The char sequence for the `inject top` command
+    pub const INJECT_TOP: &'static str = "inject-top";
+    /// The char sequence for the `inject at page` command
+    pub const INJECT_AT_PAGE: &'static str = "inject-at";
     /// The char sequence for the `wrap image` command
     pub const WRAP_IMAGE: &'static str = "image";
     /// The char sequence for the `class` command
     pub const PAGE_CLASS: &'static str = "class";
+    /// The char sequence for the `clear class` command
+    pub const CLEAR_CLASS: &'static str = "clear-class";
+    /// The char sequence for the `sticky class` command
+    pub const STICKY_PAGE_CLASS: &'static str = "class-sticky";
+    /// The char sequence for the `page id` command
+    pub const PAGE_ID: &'static str = "id";
+    /// The char sequence for the `columns` command
+    pub const COLUMNS: &'static str = "columns";
+    /// The delimiter used by the `columns` command if none is defined
+    pub const COLUMNS_DELIMITER: &'static str = "|||";
+    /// The char sequence for the `include` command
+    pub const INCLUDE: &'static str = "include";
+    /// The char sequence for the `set variable` command
+    pub const SET_VARIABLE: &'static str = "set";
+    /// The char sequence for the `fragment` command
+    pub const FRAGMENT: &'static str = "fragment";
+    /// The char sequence for the `keywords` command
+    pub const PAGE_KEYWORDS: &'static str = "keywords";
+    /// The char sequence for the `code` command
+    pub const CODE: &'static str = "code";
+    /// The char sequence for the `add stream to page` command
+    pub const ADD_STREAM_TO_PAGE: &'static str = "output";
+    /// The char sequence for the `add error to page` command
+    pub const ADD_ERROR_TO_PAGE: &'static str = "error";
+    /// The char sequence for the `add image to page` command
+    pub const ADD_IMAGE_TO_PAGE: &'static str = "output-image";
+    /// The char sequence for the `raw` command
+    pub const RAW: &'static str = "raw";
+    /// The char sequence for the `auto split` command
+    pub const AUTO_SPLIT: &'static str = "autosplit";
+    /// The char sequence for the `if profile` command
+    pub const IF_PROFILE: &'static str = "if-profile";
+    /// The char sequence for the `end if` command
+    pub const END_IF: &'static str = "end-if";
+    /// The char sequence for the `html` command
+    pub const HTML: &'static str = "html";
+    /// The char sequence for the `presenter only` command
+    pub const PRESENTER_ONLY: &'static str = "presenter-only";
+    /// The char sequence for the `trim` command
+    pub const TRIM: &'static str = "trim";
+
+    /// The char sequences of all commands
+    pub const KEYWORDS: &'static [&'static str] = &[
+        Command::NEW_PAGE,
+        Command::START_ADD_TO_PAGE,
+        Command::STOP_ADD_TO_PAGE,
+        Command::INJECT_TP_PAGE,
+        Command::INJECT_AT_PAGE,
+        Command::INJECT_TOP,
+        Command::WRAP_IMAGE,
+        Command::PAGE_CLASS,
+        Command::CLEAR_CLASS,
+        Command::STICKY_PAGE_CLASS,
+        Command::PAGE_ID,
+        Command::COLUMNS,
+        Command::INCLUDE,
+        Command::SET_VARIABLE,
+        Command::FRAGMENT,
+        Command::PAGE_KEYWORDS,
+        Command::CODE,
+        Command::ADD_STREAM_TO_PAGE,
+        Command::ADD_ERROR_TO_PAGE,
+        Command::ADD_IMAGE_TO_PAGE,
+        Command::RAW,
+        Command::AUTO_SPLIT,
+        Command::IF_PROFILE,
+        Command::END_IF,
+        Command::PRESENTER_ONLY,
+        Command::HTML,
+        Command::TRIM,
+    ];
+    /// Alternative char sequences and the command char sequence they stand for
+    pub const ALIASES: &'static [(&'static str, &'static str)] = &[
+        ("slide", Command::NEW_PAGE),
+        ("add", Command::START_ADD_TO_PAGE),
+        ("append", Command::INJECT_TP_PAGE),
+        ("prepend", Command::INJECT_TOP),
+    ];
+}
+
+/// Calculates the edit distance between two strings, i.e. the number of
+/// inserted, removed or replaced chars to turn one into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut distances = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut previous = distances[0];
+        distances[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let current = distances[j + 1];
+            distances[j + 1] = if a == *b {
+                previous
+            } else {
+                1 + previous.min(current).min(distances[j])
+            };
+            previous = current;
+        }
+    }
+
+    distances[b.len()]
+}
+
+/// Returns the known command char sequence closest to the given unknown
+/// one, if it is similar enough to be a typo.
+fn closest_keyword(unknown: &str) -> Option<&'static str> {
+    let unknown = unknown.to_lowercase();
+    Command::KEYWORDS
+        .iter()
+        .chain(Command::ALIASES.iter().map(|(alias, _)| alias))
+        .map(|keyword| (edit_distance(&unknown, keyword), *keyword))
+        .filter(|(distance, keyword)| *distance <= 2 && *distance < keyword.len())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, keyword)| keyword)
+}
+
+/// Parses the char sequence of a command. The case is ignored and aliases
+/// are resolved to the char sequence of the command they stand for.
+fn parse_keyword() -> impl Parser<char, String, Error = Simple<char>> {
+    filter(|c: &char| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .repeated()
+        .at_least(1)
+        .collect::<String>()
+        .map(|keyword| {
+            let keyword = keyword.to_lowercase();
+            Command::ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == keyword)
+                .map(|(_, name)| name.to_string())
+                .unwrap_or(keyword)
+        })
+}
+
+/// A Parser that only accepts the char sequence `name` of a command.
+fn keyword(name: &'static str) -> impl Parser<char, &'static str, Error = Simple<char>> {
+    parse_keyword().try_map(move |keyword, span| {
+        if keyword == name {
+            Ok(name)
+        } else {
+            Err(Simple::custom(span, format!("Expected '{name}'")))
+        }
+    })
 }
 
-/// Parse a list of contents in case of nested `[...]`
+/// Parse the content of a `[...]` block. Balanced nested brackets are part of
+/// the content, while escaped brackets `\[` and `\]` don't need to be balanced.
+/// Returns `None` if the brackets are not balanced.
 fn parse_content() -> impl Parser<char, Option<String>, Error = Simple<char>> {
-    let empty = just(']').ignored().rewind().to(vec![]);
-    let content = take_until(
-        none_of('\\')
-            .then(just('[').or(just(']')))
-            .ignored()
-            .rewind(),
-    )
-    .map(|(s, _)| s)
-    .chain(any());
-
-    let text = just('[')
-        .then(empty.or(content))
-        .then(just(']'))
-        .map(|((_, s), _)| s);
+    let content = recursive(|content| {
+        let escaped = just('\\').chain(one_of("[]"));
+        let nested = just('[').chain(content).chain(just(']'));
+        let char = none_of("[]").map(|c| vec![c]);
+
+        escaped.or(nested).or(char).repeated().flatten()
+    });
+
+    let text = just('[').ignore_then(content).then_ignore(just(']'));
 
     text.collect::<String>()
         .map(|f| {
@@ -103,23 +323,89 @@ fn parse_content() -> impl Parser<char, Option<String>, Error = Simple<char>> {
 
 /// A Parser that only parse to [`Command::NewPage`].
 fn parse_new_page_command() -> impl Parser<char, Command, Error = Simple<char>> {
-    just(Command::NEW_PAGE).to(Command::NewPage)
+    keyword(Command::NEW_PAGE).to(Command::NewPage)
 }
 
 /// A Parser that only parse to [`Command::StartAddToPage`].
 fn parse_start_add_to_page_command() -> impl Parser<char, Command, Error = Simple<char>> {
-    just(Command::START_ADD_TO_PAGE).to(Command::StartAddToPage)
+    keyword(Command::START_ADD_TO_PAGE).to(Command::StartAddToPage)
 }
 
 /// A Parser that only parse to [`Command::StopAddToPage`].
 fn parse_stop_add_to_page_command() -> impl Parser<char, Command, Error = Simple<char>> {
-    just(Command::STOP_ADD_TO_PAGE).to(Command::StopAddToPage)
+    keyword(Command::STOP_ADD_TO_PAGE).to(Command::StopAddToPage)
+}
+
+/// A Parser that only parse to [`Command::Fragment`].
+fn parse_fragment_command() -> impl Parser<char, Command, Error = Simple<char>> {
+    keyword(Command::FRAGMENT).to(Command::Fragment)
+}
+
+/// A Parser that only parse to [`Command::AutoSplit`].
+fn parse_auto_split_command() -> impl Parser<char, Command, Error = Simple<char>> {
+    keyword(Command::AUTO_SPLIT).to(Command::AutoSplit)
+}
+
+/// A Parser that only parse to [`Command::EndIf`].
+fn parse_end_if_command() -> impl Parser<char, Command, Error = Simple<char>> {
+    keyword(Command::END_IF).to(Command::EndIf)
+}
+
+/// A Parser that only parse to [`Command::ClearClass`].
+fn parse_clear_class_command() -> impl Parser<char, Command, Error = Simple<char>> {
+    keyword(Command::CLEAR_CLASS).to(Command::ClearClass)
+}
+
+/// A Parser that only parse to [`Command::Trim`].
+fn parse_trim_command() -> impl Parser<char, Command, Error = Simple<char>> {
+    keyword(Command::TRIM).to(Command::Trim)
+}
+
+/// A Parser that only parse to [`Command::PresenterOnly`].
+fn parse_presenter_only_command() -> impl Parser<char, Command, Error = Simple<char>> {
+    keyword(Command::PRESENTER_ONLY).to(Command::PresenterOnly)
+}
+
+/// A Parser that only parse to [`Command::Code`].
+fn parse_code_command() -> impl Parser<char, Command, Error = Simple<char>> {
+    keyword(Command::CODE).to(Command::Code)
+}
+
+/// A Parser that only parse to [`Command::AddStreamToPage`].
+fn parse_add_stream_to_page_command() -> impl Parser<char, Command, Error = Simple<char>> {
+    keyword(Command::ADD_STREAM_TO_PAGE).to(Command::AddStreamToPage)
+}
+
+/// A Parser that only parse to [`Command::AddErrorToPage`].
+fn parse_add_error_to_page_command() -> impl Parser<char, Command, Error = Simple<char>> {
+    keyword(Command::ADD_ERROR_TO_PAGE).to(Command::AddErrorToPage)
+}
+
+/// A Parser that only parse to [`Command::AddImageToPage`].
+fn parse_add_image_to_page_command() -> impl Parser<char, Command, Error = Simple<char>> {
+    keyword(Command::ADD_IMAGE_TO_PAGE).to(Command::AddImageToPage)
+}
+
+/// A Parser that only parse to [`Command::Raw`].
+fn parse_raw_command() -> impl Parser<char, Command, Error = Simple<char>> {
+    keyword(Command::RAW).to(Command::Raw)
+}
+
+/// A Parser that only parse to [`Command::InjectTop`].
+fn parse_inject_top_command() -> impl Parser<char, Result<Command, ParseError>, Error = Simple<char>>
+{
+    keyword(Command::INJECT_TOP)
+        .then(parse_content().padded())
+        .map(|(name, content)| match content {
+            Some(some) => Ok(Command::InjectTop(some)),
+            None => Err(ParseError::Content(name.to_string())),
+        })
 }
 
 /// A Parser that only parse to [`Command::InjectToPage`].
 fn parse_inject_to_page_command(
 ) -> impl Parser<char, Result<Command, ParseError>, Error = Simple<char>> {
-    just(Command::INJECT_TP_PAGE)
+    keyword(Command::INJECT_TP_PAGE)
         .then(parse_content().padded())
         .map(|(name, content)| match content {
             Some(some) => Ok(Command::InjectToPage(some)),
@@ -127,10 +413,24 @@ fn parse_inject_to_page_command(
         })
 }
 
+/// A Parser that only parse to [`Command::InjectAtPage`].
+fn parse_inject_at_page_command(
+) -> impl Parser<char, Result<Command, ParseError>, Error = Simple<char>> {
+    keyword(Command::INJECT_AT_PAGE)
+        .then(parse_content().padded())
+        .then(parse_content().padded())
+        .map(|((name, offset), content)| {
+            match (offset.map(|offset| offset.trim().parse::<isize>()), content) {
+                (Some(Ok(offset)), Some(content)) => Ok(Command::InjectAtPage(offset, content)),
+                _ => Err(ParseError::Content(name.to_string())),
+            }
+        })
+}
+
 /// A Parser that only parse to [`Command::WrapImage`].
 fn parse_wrap_image_command() -> impl Parser<char, Result<Command, ParseError>, Error = Simple<char>>
 {
-    just(Command::WRAP_IMAGE)
+    keyword(Command::WRAP_IMAGE)
         .then(parse_content().padded())
         .map(|(name, content)| match content {
             Some(some) => Ok(Command::WrapImage(some)),
@@ -141,7 +441,7 @@ fn parse_wrap_image_command() -> impl Parser<char, Result<Command, ParseError>,
 /// A Parser that only parse to [`Command::PageClass`].
 fn parse_page_class_command() -> impl Parser<char, Result<Command, ParseError>, Error = Simple<char>>
 {
-    just(Command::PAGE_CLASS)
+    keyword(Command::PAGE_CLASS)
         .then(parse_content().padded())
         .map(|(name, content)| match content {
             Some(some) => Ok(Command::PageClass(some.trim().to_string())),
@@ -149,27 +449,165 @@ fn parse_page_class_command() -> impl Parser<char, Result<Command, ParseError>,
         })
 }
 
+/// A Parser that only parse to [`Command::StickyPageClass`].
+fn parse_sticky_page_class_command(
+) -> impl Parser<char, Result<Command, ParseError>, Error = Simple<char>> {
+    keyword(Command::STICKY_PAGE_CLASS)
+        .then(parse_content().padded())
+        .map(|(name, content)| match content {
+            Some(some) => Ok(Command::StickyPageClass(some.trim().to_string())),
+            None => Err(ParseError::Content(name.to_string())),
+        })
+}
+
+/// A Parser that only parse to [`Command::PageId`].
+fn parse_page_id_command() -> impl Parser<char, Result<Command, ParseError>, Error = Simple<char>> {
+    keyword(Command::PAGE_ID)
+        .then(parse_content().padded())
+        .map(|(name, content)| match content {
+            Some(some) if !some.trim().is_empty() => Ok(Command::PageId(some.trim().to_string())),
+            _ => Err(ParseError::Content(name.to_string())),
+        })
+}
+
+/// A Parser that only parse to [`Command::Html`].
+fn parse_html_command() -> impl Parser<char, Result<Command, ParseError>, Error = Simple<char>> {
+    keyword(Command::HTML)
+        .then(parse_content().padded())
+        .map(|(name, content)| match content {
+            Some(some) => Ok(Command::Html(some)),
+            None => Err(ParseError::Content(name.to_string())),
+        })
+}
+
+/// A Parser that only parse to [`Command::IfProfile`].
+fn parse_if_profile_command() -> impl Parser<char, Result<Command, ParseError>, Error = Simple<char>>
+{
+    keyword(Command::IF_PROFILE)
+        .then(parse_content().padded())
+        .map(|(name, content)| match content {
+            Some(some) if !some.trim().is_empty() => {
+                Ok(Command::IfProfile(some.trim().to_string()))
+            }
+            _ => Err(ParseError::Content(name.to_string())),
+        })
+}
+
+/// A Parser that only parse to [`Command::Columns`].
+fn parse_columns_command() -> impl Parser<char, Result<Command, ParseError>, Error = Simple<char>> {
+    keyword(Command::COLUMNS)
+        .then(parse_content().padded())
+        .map(|(name, content)| match content {
+            Some(some) if some.trim().is_empty() => {
+                Ok(Command::Columns(Command::COLUMNS_DELIMITER.to_string()))
+            }
+            Some(some) => Ok(Command::Columns(some.trim().to_string())),
+            None => Err(ParseError::Content(name.to_string())),
+        })
+}
+
+/// A Parser that only parse to [`Command::Include`].
+fn parse_include_command() -> impl Parser<char, Result<Command, ParseError>, Error = Simple<char>> {
+    keyword(Command::INCLUDE)
+        .then(parse_content().padded())
+        .map(|(name, content)| match content {
+            Some(some) => Ok(Command::Include(some.trim().to_string())),
+            None => Err(ParseError::Content(name.to_string())),
+        })
+}
+
+/// A Parser that only parse to [`Command::SetVariable`].
+fn parse_set_variable_command(
+) -> impl Parser<char, Result<Command, ParseError>, Error = Simple<char>> {
+    keyword(Command::SET_VARIABLE)
+        .then(parse_content().padded())
+        .map(
+            |(name, content)| match content.as_ref().and_then(|content| content.split_once('=')) {
+                Some((variable, value)) if !variable.trim().is_empty() => Ok(Command::SetVariable(
+                    variable.trim().to_string(),
+                    value.trim().to_string(),
+                )),
+                _ => Err(ParseError::Content(name.to_string())),
+            },
+        )
+}
+
+/// A Parser that only parse to [`Command::Keywords`]. The keywords are
+/// separated by a `,`.
+fn parse_keywords_command() -> impl Parser<char, Result<Command, ParseError>, Error = Simple<char>>
+{
+    keyword(Command::PAGE_KEYWORDS)
+        .then(parse_content().padded())
+        .map(|(name, content)| {
+            let keywords = content
+                .map(|content| {
+                    content
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|keyword| !keyword.is_empty())
+                        .map(str::to_string)
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            if keywords.is_empty() {
+                Err(ParseError::Content(name.to_string()))
+            } else {
+                Ok(Command::Keywords(keywords))
+            }
+        })
+}
+
 /// A parser that parse to [`Command`]
 fn parse_command() -> impl Parser<char, Result<Command, ParseError>, Error = Simple<char>> {
     parse_new_page_command()
         .or(parse_start_add_to_page_command())
         .or(parse_stop_add_to_page_command())
+        .or(parse_fragment_command())
+        .or(parse_code_command())
+        .or(parse_add_stream_to_page_command())
+        .or(parse_add_error_to_page_command())
+        .or(parse_add_image_to_page_command())
+        .or(parse_raw_command())
+        .or(parse_auto_split_command())
+        .or(parse_end_if_command())
+        .or(parse_presenter_only_command())
+        .or(parse_clear_class_command())
+        .or(parse_trim_command())
         .map(Ok)
+        .or(parse_inject_at_page_command())
+        .or(parse_inject_top_command())
         .or(parse_inject_to_page_command())
         .or(parse_wrap_image_command())
         .or(parse_page_class_command())
-        .or(text::ident().map(|f| Err(ParseError::UnknownCommand(f))))
+        .or(parse_sticky_page_class_command())
+        .or(parse_page_id_command())
+        .or(parse_columns_command())
+        .or(parse_include_command())
+        .or(parse_set_variable_command())
+        .or(parse_keywords_command())
+        .or(parse_if_profile_command())
+        .or(parse_html_command())
+        .or(parse_keyword().map(|f| Err(ParseError::UnknownCommand(f))))
 }
-/// A parser that parse to [`Vec<Command>`]
+
+/// A parser that parse to [`Vec<Command>`]. The `;` after the last command is
+/// optional.
 fn parse_commands(
-) -> impl Parser<char, (Result<Vec<Command>, ParseError>, String), Error = Simple<char>> {
+) -> impl Parser<char, (Result<Vec<Command>, ParseError>, (String, usize)), Error = Simple<char>> {
     parse_command()
         .then(
             just(';')
                 .ignored()
                 .to(Ok(()))
-                .or(take_until(end())
-                    .map(|(s, _)| Err(ParseError::MissingComma(s.into_iter().collect()))))
+                .or(end().to(Ok(())))
+                .or(
+                    take_until(end()).map_with_span(|(s, _), span: Range<usize>| {
+                        Err(ParseError::MissingComma(
+                            s.into_iter().collect(),
+                            span.start,
+                        ))
+                    }),
+                )
                 .padded(),
         )
         .map(|(command, comma)| match (command, comma) {
@@ -180,7 +618,10 @@ fn parse_commands(
         })
         .repeated()
         .collect()
-        .then(take_until(end()).map(|(s, _)| s).collect())
+        .then(
+            take_until(end())
+                .map_with_span(|(s, _), span: Range<usize>| (s.into_iter().collect(), span.start)),
+        )
         .padded()
 }
 
@@ -192,9 +633,9 @@ fn parse_commands(
 /// Returns a `ParseError` if the `stream` input could not be fully parsed.
 pub fn parse(stream: &str) -> Result<Vec<Command>, ParseError> {
     match parse_commands().parse(stream) {
-        Ok((result, end)) => {
+        Ok((result, (end, offset))) => {
             if result.is_ok() && !end.is_empty() {
-                Err(ParseError::Remaining(end))
+                Err(ParseError::Remaining(end, offset))
             } else {
                 result
             }
@@ -210,6 +651,7 @@ mod test {
     use crate::commands::{
         parse,
         Command::{self, *},
+        ParseError,
     };
 
     use super::parse_content;
@@ -233,6 +675,15 @@ mod test {
         assert_eq!(result, Ok(None));
         let result = parser.parse(r"[content ]]");
         assert_eq!(result, Ok(Some(r"content ".to_string())));
+
+        let result = parser.parse(r"[a [b] c]");
+        assert_eq!(result, Ok(Some(r"a [b] c".to_string())));
+        let result = parser.parse(r"[a [b [c] d] e]");
+        assert_eq!(result, Ok(Some(r"a [b [c] d] e".to_string())));
+        let result = parser.parse(r"[a [b]");
+        assert_eq!(result, Ok(None));
+        let result = parser.parse(r"[[link](url) \]]");
+        assert_eq!(result, Ok(Some(r"[link](url) ]".to_string())));
     }
 
     #[test]
@@ -251,6 +702,14 @@ mod test {
         {} [
             class
         ];
+        {}[ ./disclaimer.md ];
+        {};
+        {};
+        {};
+        {};
+        {};
+        {};
+        {};
         "#,
             Command::NEW_PAGE,
             Command::START_ADD_TO_PAGE,
@@ -258,6 +717,14 @@ mod test {
             Command::INJECT_TP_PAGE,
             Command::WRAP_IMAGE,
             Command::PAGE_CLASS,
+            Command::INCLUDE,
+            Command::FRAGMENT,
+            Command::CODE,
+            Command::ADD_STREAM_TO_PAGE,
+            Command::ADD_ERROR_TO_PAGE,
+            Command::ADD_IMAGE_TO_PAGE,
+            Command::RAW,
+            Command::AUTO_SPLIT,
         ));
 
         assert_eq!(
@@ -269,6 +736,14 @@ mod test {
                 InjectToPage("\n            content\n        ".to_string()),
                 WrapImage("\n            content\n        ".to_string()),
                 PageClass("class".to_string()),
+                Include("./disclaimer.md".to_string()),
+                Fragment,
+                Code,
+                AddStreamToPage,
+                AddErrorToPage,
+                AddImageToPage,
+                Raw,
+                AutoSplit,
             ])
         );
 
@@ -277,6 +752,76 @@ mod test {
         assert_eq!(result, Ok(vec![NewPage,]));
     }
 
+    #[test]
+    fn test_case_and_aliases() {
+        assert_eq!(parse("NEW;"), Ok(vec![NewPage]));
+        assert_eq!(parse("new;"), Ok(vec![NewPage]));
+        assert_eq!(parse("slide;"), Ok(vec![NewPage]));
+        assert_eq!(parse("Start-Add;"), Ok(vec![StartAddToPage]));
+        assert_eq!(
+            parse("append[content];"),
+            Ok(vec![InjectToPage("content".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_unknown_command() {
+        let result = parse("nwe;");
+        assert_eq!(result, Err(ParseError::UnknownCommand("nwe".to_string())));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unknown command 'nwe'. Did you mean 'new'? "
+        );
+        let result = parse("something;");
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unknown command 'something'. "
+        );
+    }
+
+    #[test]
+    fn test_optional_last_semicolon() {
+        let result = parse(&format!(" {} ", Command::NEW_PAGE));
+        assert_eq!(result, Ok(vec![NewPage]));
+        let result = parse(&format!(
+            "{}; {}[center]",
+            Command::NEW_PAGE,
+            Command::PAGE_CLASS
+        ));
+        assert_eq!(result, Ok(vec![NewPage, PageClass("center".to_string())]));
+        let result = parse(&format!(
+            "{} {}",
+            Command::NEW_PAGE,
+            Command::START_ADD_TO_PAGE
+        ));
+        assert_eq!(
+            result,
+            Err(ParseError::MissingComma(
+                Command::START_ADD_TO_PAGE.to_string(),
+                4
+            ))
+        );
+    }
+
+    #[test]
+    fn test_error_offset() {
+        let result = parse(&format!(
+            "{}; {} {}[center];",
+            Command::NEW_PAGE,
+            Command::START_ADD_TO_PAGE,
+            Command::PAGE_CLASS
+        ));
+        let err = result.unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::MissingComma(format!("{}[center];", Command::PAGE_CLASS), 15)
+        );
+        assert!(err.to_string().ends_with("at offset 15. "));
+
+        let result = parse(&format!("{};  ?", Command::NEW_PAGE));
+        assert_eq!(result, Err(ParseError::Remaining("?".to_string(), 6)));
+    }
+
     #[test]
     fn test_wrap_image() {
         let result = parse(&format!("{}[content];", Command::WRAP_IMAGE));
@@ -284,4 +829,179 @@ mod test {
         let result = parse(&format!("{}[!\\[\\]({})];", Command::WRAP_IMAGE, "{}"));
         assert_eq!(result, Ok(vec![Command::WrapImage("![]({})".to_string())]));
     }
+
+    #[test]
+    fn test_set_variable() {
+        let result = parse(&format!("{}[ name = some value ];", Command::SET_VARIABLE));
+        assert_eq!(
+            result,
+            Ok(vec![Command::SetVariable(
+                "name".to_string(),
+                "some value".to_string()
+            )])
+        );
+        let result = parse(&format!("{}[name];", Command::SET_VARIABLE));
+        assert_eq!(
+            result,
+            Err(ParseError::Content(Command::SET_VARIABLE.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_sticky_page_class() {
+        let result = parse(&format!(
+            "{}[ section ]; {}[];",
+            Command::STICKY_PAGE_CLASS,
+            Command::STICKY_PAGE_CLASS
+        ));
+        assert_eq!(
+            result,
+            Ok(vec![
+                Command::StickyPageClass("section".to_string()),
+                Command::StickyPageClass(String::new())
+            ])
+        );
+    }
+
+    #[test]
+    fn test_keywords() {
+        let result = parse(&format!("{}[ fire, , plume ];", Command::PAGE_KEYWORDS));
+        assert_eq!(
+            result,
+            Ok(vec![Command::Keywords(vec![
+                "fire".to_string(),
+                "plume".to_string()
+            ])])
+        );
+        let result = parse(&format!("{}[ , ];", Command::PAGE_KEYWORDS));
+        assert_eq!(
+            result,
+            Err(ParseError::Content(Command::PAGE_KEYWORDS.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_page_id() {
+        let result = parse(&format!("{}[ intro ];", Command::PAGE_ID));
+        assert_eq!(result, Ok(vec![Command::PageId("intro".to_string())]));
+        let result = parse(&format!("{}[]", Command::PAGE_ID));
+        assert_eq!(
+            result,
+            Err(ParseError::Content(Command::PAGE_ID.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        let result = parse(&format!(r#"{}[<img src="./x.png">];"#, Command::HTML));
+        assert_eq!(
+            result,
+            Ok(vec![Command::Html(r#"<img src="./x.png">"#.to_string())])
+        );
+        let result = parse(Command::HTML);
+        assert_eq!(result, Err(ParseError::Content(Command::HTML.to_string())));
+    }
+
+    #[test]
+    fn test_clear_class() {
+        let result = parse(&format!(
+            "{}[center]; {};",
+            Command::PAGE_CLASS,
+            Command::CLEAR_CLASS
+        ));
+        assert_eq!(
+            result,
+            Ok(vec![
+                Command::PageClass("center".to_string()),
+                Command::ClearClass
+            ])
+        );
+    }
+
+    #[test]
+    fn test_if_profile() {
+        let result = parse(&format!(
+            "{}[ instructor ]; new; {}",
+            Command::IF_PROFILE,
+            Command::END_IF
+        ));
+        assert_eq!(
+            result,
+            Ok(vec![
+                Command::IfProfile("instructor".to_string()),
+                Command::NewPage,
+                Command::EndIf
+            ])
+        );
+        let result = parse(Command::IF_PROFILE);
+        assert_eq!(
+            result,
+            Err(ParseError::Content(Command::IF_PROFILE.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_trim() {
+        let result = parse(Command::TRIM);
+        assert_eq!(result, Ok(vec![Command::Trim]));
+        let result = parse(&format!("{}; {}", Command::NEW_PAGE, Command::TRIM));
+        assert_eq!(result, Ok(vec![Command::NewPage, Command::Trim]));
+    }
+
+    #[test]
+    fn test_presenter_only() {
+        let result = parse(Command::PRESENTER_ONLY);
+        assert_eq!(result, Ok(vec![Command::PresenterOnly]));
+        let result = parse(&format!(
+            "{}; {};",
+            Command::NEW_PAGE,
+            Command::PRESENTER_ONLY
+        ));
+        assert_eq!(result, Ok(vec![Command::NewPage, Command::PresenterOnly]));
+    }
+
+    #[test]
+    fn test_inject_top() {
+        let result = parse(&format!("{}[content];", Command::INJECT_TOP));
+        assert_eq!(result, Ok(vec![Command::InjectTop("content".to_string())]));
+        let result = parse("Prepend[content]");
+        assert_eq!(result, Ok(vec![Command::InjectTop("content".to_string())]));
+        let result = parse(Command::INJECT_TOP);
+        assert_eq!(
+            result,
+            Err(ParseError::Content(Command::INJECT_TOP.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_inject_at_page() {
+        let result = parse(&format!("{}[-1][content];", Command::INJECT_AT_PAGE));
+        assert_eq!(
+            result,
+            Ok(vec![Command::InjectAtPage(-1, "content".to_string())])
+        );
+        let result = parse(&format!("{}[ 0 ] [content];", Command::INJECT_AT_PAGE));
+        assert_eq!(
+            result,
+            Ok(vec![Command::InjectAtPage(0, "content".to_string())])
+        );
+        let result = parse(&format!("{}[last][content];", Command::INJECT_AT_PAGE));
+        assert_eq!(
+            result,
+            Err(ParseError::Content(Command::INJECT_AT_PAGE.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_columns() {
+        let result = parse(&format!("{}[];", Command::COLUMNS));
+        assert_eq!(
+            result,
+            Ok(vec![Command::Columns(
+                Command::COLUMNS_DELIMITER.to_string()
+            )])
+        );
+        let result = parse(&format!("{}[ *** ];", Command::COLUMNS));
+        assert_eq!(result, Ok(vec![Command::Columns("***".to_string())]));
+    }
 }
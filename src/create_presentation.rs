@@ -1,54 +1,1426 @@
 //! creates a presentation by stitching together the generated pages from a
 //! notebook or file.
 use anyhow::Result;
-use std::{fs::File, io::Write, path::PathBuf};
+use log::{debug, error, warn};
+use rayon::prelude::*;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::SystemTime,
+};
 
-use crate::notebook::Notebook;
+use crate::{
+    format::{normalize_math, split_into_slides, split_properties, Format, PAGE_SEPARATOR},
+    notebook::{Notebook, NotebookOptions, Page, TITLE_VARIABLE},
+    path::replace_paths,
+    report,
+};
+
+/// The path that stands for `stdin` as input and `stdout` as output.
+pub const STDIO: &str = "-";
+
+/// The page of a notebook in the [`PageCache`] with the modification times of
+/// the notebook and the files it includes at the time it was converted.
+#[derive(Debug)]
+struct CachedPage {
+    /// The notebook and the included files with their modification time.
+    modified: Vec<(PathBuf, Option<SystemTime>)>,
+    /// The converted page of the notebook.
+    page: String,
+    /// The pages of the notebook the `page` was written from.
+    notebook_pages: Vec<Page>,
+}
+
+impl CachedPage {
+    /// Returns `true` if neither the notebook nor an included file changed
+    /// since the page was converted.
+    fn is_fresh(&self) -> bool {
+        self.modified
+            .iter()
+            .all(|(path, modified)| modified_time(path) == *modified)
+    }
+}
+
+/// Returns the modification time of the file at the `path` or `None`, if the
+/// file does not exist.
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Caches the converted pages of the notebooks between the rebuilds of
+/// `--watch`, keyed by the output and notebook path. A page is converted
+/// again, if the notebook or a file it includes was modified.
+#[derive(Debug, Default)]
+pub struct PageCache {
+    /// The cached pages by the output and notebook path.
+    pages: Mutex<HashMap<(PathBuf, PathBuf), CachedPage>>,
+    /// The number of notebooks that were converted, because their page was
+    /// not cached or outdated.
+    conversions: AtomicUsize,
+}
+
+impl PageCache {
+    /// Returns the number of notebooks that were converted so far.
+    pub fn conversions(&self) -> usize {
+        self.conversions.load(Ordering::Relaxed)
+    }
+
+    /// Reads the page of a notebook like [`read_page_vec`], but reuses the
+    /// cached page if the notebook is unchanged. Other files and `stdin` are
+    /// not cached.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the file could not be read or the
+    /// notebook could not be converted.
+    fn read_page(
+        &self,
+        output_path: &Path,
+        options: &NotebookOptions,
+        path: &PathBuf,
+    ) -> Result<(String, Vec<Page>)> {
+        if path == Path::new(STDIO) || path.extension().is_none_or(|ext| ext != "ipynb") {
+            return read_page_vec(output_path, options, path);
+        }
+
+        let key = (output_path.to_path_buf(), path.clone());
+        if let Ok(pages) = self.pages.lock() {
+            if let Some(cached) = pages.get(&key).filter(|cached| cached.is_fresh()) {
+                debug!("Reuse the page of the unchanged notebook {path:?}");
+                return Ok((cached.page.clone(), cached.notebook_pages.clone()));
+            }
+        }
+
+        let modified = modified_time(path);
+        let (notebook_pages, included) =
+            Notebook::try_from_path(path)?.into_page_vec(output_path, options)?;
+        let page = Page::join(&notebook_pages, options.separator());
+        self.conversions.fetch_add(1, Ordering::Relaxed);
+        let modified = std::iter::once((path.clone(), modified))
+            .chain(included.into_iter().map(|path| {
+                let modified = modified_time(&path);
+                (path, modified)
+            }))
+            .collect();
+        if let Ok(mut pages) = self.pages.lock() {
+            pages.insert(
+                key,
+                CachedPage {
+                    modified,
+                    page: page.clone(),
+                    notebook_pages: notebook_pages.clone(),
+                },
+            );
+        }
+        Ok((page, notebook_pages))
+    }
+}
+
+/// Creates the file at the `output_path` or returns `stdout`, if the path is
+/// [`STDIO`].
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be created.
+fn create_output(output_path: &Path) -> Result<Box<dyn Write>> {
+    if output_path == Path::new(STDIO) {
+        Ok(Box::new(io::stdout()))
+    } else {
+        Ok(Box::new(File::create(output_path)?))
+    }
+}
 
 /// This function takes a slice of [`PathBuf`] paths as input. If a given path
 /// corresponds to a `.ipynb` file, the function attempts to read it as a
 /// notebook and create pages from it.  If the path corresponds to a file of
-/// another type, the function reads and passes it in completely.
+/// another type, the function reads and passes it in completely, with the
+/// `@import` lines replaced by the imported files, see [`read_file`]. The
+/// notebooks are converted with the given `options`. Paths without an
+/// extension result in an empty page, so every page belongs to the path at
+/// the same index. The path [`STDIO`] reads a notebook from `stdin`.
+///
+/// The files are converted in parallel, but the pages keep the order of the
+/// `paths`. A file that could not be converted is logged and results in an
+/// empty page, unless [`NotebookOptions::strict`] is set. With a `cache`,
+/// the pages of unchanged notebooks are reused, see [`PageCache`]. The
+/// [`Page`]s of every notebook are returned along the pages at the same
+/// index, the other files have none.
 ///
 /// # Errors
 ///
-/// This function will return an error if:
+/// This function will return an error if every file failed or, with
+/// [`NotebookOptions::strict`], if any file failed, because:
 /// - `output_path` does not already exist.
 /// - the notebook file could not be read or not parsed from json.
 /// - either the output or notebook path has no parent.
-pub fn collect_pages(output_path: PathBuf, paths: &[PathBuf]) -> Result<Vec<String>> {
+pub fn collect_pages(
+    output_path: PathBuf,
+    options: &NotebookOptions,
+    paths: &[PathBuf],
+    cache: Option<&PageCache>,
+) -> Result<(Vec<String>, Vec<Vec<Page>>)> {
+    let results = paths
+        .par_iter()
+        .map(|path| match cache {
+            Some(cache) => cache.read_page(&output_path, options, path),
+            None => read_page_vec(&output_path, options, path),
+        })
+        .collect::<Vec<_>>();
+
     let mut pages = vec![];
+    let mut notebook_pages = vec![];
+    let mut failed = 0;
+    for (path, result) in paths.iter().zip(results) {
+        let result = match result {
+            Ok((page, pages)) => {
+                notebook_pages.push(pages);
+                Ok(page)
+            }
+            Err(err) => {
+                notebook_pages.push(vec![]);
+                Err(err)
+            }
+        };
+        match checked_page(path, result, options.strict)? {
+            Some(page) => pages.push(page),
+            None => {
+                failed += 1;
+                pages.push(String::new());
+            }
+        }
+    }
+
+    check_failed(failed, paths.len())?;
+    let mut ids = PageIds::default();
+    for page in &pages {
+        ids.add(page, options.separator());
+    }
+    ids.warn_duplicates();
+    Ok((pages, notebook_pages))
+}
+
+/// Checks the `result` of reading the file at the `path` for
+/// [`collect_pages`] and [`stream_presentation`]. A failed file is logged
+/// and results in `None`, unless `strict` is set. A notebook without pages
+/// is logged as well.
+///
+/// # Errors
+///
+/// This function will return the error of the `result`, if `strict` is set.
+fn checked_page(path: &Path, result: Result<String>, strict: bool) -> Result<Option<String>> {
+    match result {
+        Ok(page) => {
+            if page.trim().is_empty() && path.extension().is_some_and(|ext| ext == "ipynb") {
+                warn!("File: {:?}. The notebook has no pages.", path);
+            }
+            Ok(Some(page))
+        }
+        Err(err) if strict => Err(err),
+        Err(err) => {
+            error!(
+                "File: {:?}. {}",
+                path,
+                report::render(&err, report::use_color())
+            );
+            Ok(None)
+        }
+    }
+}
+
+/// Checks if all of the `files` have `failed`.
+///
+/// # Errors
+///
+/// This function will return an error if every file failed.
+fn check_failed(failed: usize, files: usize) -> Result<()> {
+    if failed > 0 && failed == files {
+        return Err(anyhow::Error::msg(format!(
+            "None of the {failed} files could be converted."
+        )));
+    }
+    Ok(())
+}
+
+/// Converts the notebooks and files at the `paths` like [`collect_pages`]
+/// and writes a [`Format::Remark`] presentation to the `output_path` while
+/// the pages are produced, instead of holding every page in memory. The
+/// files are converted in parallel in chunks of one file per thread, which
+/// are written in the order of the `paths`, so only the pages of one chunk
+/// are held at a time. The `title` replaces its variable in the pages, the
+/// pages are separated by the [`NotebookOptions::separator`] and the
+/// `front_matter` is written in front of the first page. The written bytes
+/// are the same as with [`write_presentation`].
+///
+/// # Errors
+///
+/// This function will return an error if the output could not be written,
+/// every file failed or, with [`NotebookOptions::strict`], if any file failed.
+pub fn stream_presentation(
+    output_path: &Path,
+    options: &NotebookOptions,
+    paths: &[PathBuf],
+    cache: Option<&PageCache>,
+    title: &str,
+    front_matter: Option<&str>,
+) -> Result<()> {
+    let separator = options.separator();
+    let mut writer = BufWriter::new(create_output(output_path)?);
+    if let Some(front_matter) = front_matter {
+        write!(writer, "{}\n\n", front_matter.trim_end_matches('\n'))?;
+    }
+
+    let mut failed = 0;
+    let mut empty = true;
+    let mut ids = PageIds::default();
+    for chunk in paths.chunks(rayon::current_num_threads().max(1)) {
+        let results = chunk
+            .par_iter()
+            .map(|path| match cache {
+                Some(cache) => cache
+                    .read_page(output_path, options, path)
+                    .map(|(page, _)| page),
+                None => read_page(output_path, options, path),
+            })
+            .collect::<Vec<_>>();
+
+        for (path, result) in chunk.iter().zip(results) {
+            let Some(page) = checked_page(path, result, options.strict)? else {
+                failed += 1;
+                continue;
+            };
+            let page = normalize_math(&page, Format::Remark);
+            if page.is_empty() {
+                continue;
+            }
+            ids.add(&page, separator);
+            if !empty {
+                write!(writer, "\n{separator}\n\n")?;
+            }
+            let page = page.replace(&format!("{{{{{TITLE_VARIABLE}}}}}"), title);
+            writer.write_all(page.as_bytes())?;
+            empty = false;
+        }
+    }
+    writer.flush()?;
+
+    check_failed(failed, paths.len())?;
+    ids.warn_duplicates();
+    if empty {
+        warn!("The presentation {:?} has no content.", output_path);
+    }
+    Ok(())
+}
+
+/// Collects the ids of the `name` property of the slides page by page and
+/// keeps the ids, that are used by more than one slide.
+#[derive(Debug, Default)]
+struct PageIds {
+    /// Every id that was found.
+    ids: HashSet<String>,
+    /// The ids that were found more than once, in the order of their first
+    /// repetition.
+    duplicates: Vec<String>,
+}
+
+impl PageIds {
+    /// Adds the ids of the slides of the `page`, which are separated by the
+    /// `separator`.
+    fn add(&mut self, page: &str, separator: &str) {
+        for slide in page.split(&format!("\n{separator}\n")) {
+            let (properties, _) = split_properties(slide);
+            for (_, id) in properties.into_iter().filter(|(name, _)| *name == "name") {
+                if !self.ids.insert(id.to_string()) && !self.duplicates.iter().any(|d| d == id) {
+                    self.duplicates.push(id.to_string());
+                }
+            }
+        }
+    }
+
+    /// Logs a warning for every duplicated id.
+    fn warn_duplicates(&self) {
+        for id in &self.duplicates {
+            warn!("The page id '{id}' is used more than once, only the first page can be linked.");
+        }
+    }
+}
+
+/// Reads the page of a single notebook or file for [`stream_presentation`].
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be read or the
+/// notebook could not be converted.
+fn read_page(output_path: &Path, options: &NotebookOptions, path: &PathBuf) -> Result<String> {
+    if let Some(notebook) = read_notebook(path) {
+        return notebook?.into_pages(output_path, options);
+    }
+
+    match path.extension() {
+        Some(_) => read_file(path, &mut vec![]),
+        None => Ok(String::new()),
+    }
+}
+
+/// Reads the page of a single notebook or file for [`collect_pages`] like
+/// [`read_page`], along the [`Page`]s of a notebook.
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be read or the
+/// notebook could not be converted.
+fn read_page_vec(
+    output_path: &Path,
+    options: &NotebookOptions,
+    path: &PathBuf,
+) -> Result<(String, Vec<Page>)> {
+    if let Some(notebook) = read_notebook(path) {
+        let (pages, _) = notebook?.into_page_vec(output_path, options)?;
+        return Ok((Page::join(&pages, options.separator()), pages));
+    }
+    Ok((read_page(output_path, options, path)?, vec![]))
+}
+
+/// The keyword of a line in a file, that is not a notebook, which is replaced
+/// by the presentation at the path after it, e.g. `@import other.rmd`.
+const IMPORT: &str = "@import";
+
+/// Reads the file at the `path`, that is not a notebook, and replaces every
+/// `@import` line outside of code blocks with the content of the imported
+/// file. The path of the import is relative to the importing file and the
+/// imported file may import other files. The `imports` are the files that
+/// are being imported, to detect an import cycle.
+///
+/// # Errors
+///
+/// This function will return an error if a file could not be read, an import
+/// has no path or a file imports itself.
+fn read_file(path: &Path, imports: &mut Vec<PathBuf>) -> Result<String> {
+    let canonical = fs::canonicalize(path)
+        .map_err(|err| anyhow::Error::msg(format!("Unable to read the file {path:?}. {err}")))?;
+    if imports.contains(&canonical) {
+        return Err(anyhow::Error::msg(format!(
+            "The file {path:?} is imported by itself."
+        )));
+    }
+    let text = fs::read_to_string(path)?;
+
+    imports.push(canonical);
+    let mut content = String::with_capacity(text.len());
+    let mut in_code_block = false;
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        let import = trimmed
+            .strip_prefix(IMPORT)
+            .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+            .map(str::trim);
+        match import {
+            Some(import) if !in_code_block => {
+                if import.is_empty() {
+                    return Err(anyhow::Error::msg(format!(
+                        "File: {path:?}. The `{IMPORT}` has no path."
+                    )));
+                }
+                let import = path.parent().unwrap_or(Path::new("")).join(import);
+                content.push_str(read_file(&import, imports)?.trim_end_matches('\n'));
+                if line.ends_with('\n') {
+                    content.push('\n');
+                }
+            }
+            _ => content.push_str(line),
+        }
+    }
+    imports.pop();
+
+    Ok(content)
+}
+
+/// Reads the notebook at the `path`, or from `stdin` for the path [`STDIO`].
+/// Returns `None` if the path is not a notebook.
+fn read_notebook(path: &PathBuf) -> Option<Result<Notebook>> {
+    if path == Path::new(STDIO) {
+        return Some(
+            io::read_to_string(io::stdin())
+                .map_err(anyhow::Error::from)
+                .and_then(|text| Notebook::try_from_json(&text, path.clone())),
+        );
+    }
+
+    path.extension()
+        .is_some_and(|ext| ext == "ipynb")
+        .then(|| Notebook::try_from_path(path))
+}
+
+/// Validates the commands of the notebooks at the `paths` with the `options`
+/// without writing a presentation, see [`Notebook::validate`]. Every error is
+/// logged. Returns the number of errors.
+pub fn validate_notebooks(paths: &[PathBuf], options: &NotebookOptions) -> usize {
+    let mut count = 0;
     for path in paths {
-        if let Some(ext) = path.extension() {
-            match ext.to_str() {
-                Some("ipynb") => {
-                    let notebook = Notebook::try_from_path(path)?;
-                    pages.push(notebook.into_pages(&output_path)?);
+        match read_notebook(path) {
+            Some(Ok(notebook)) => {
+                for err in notebook.validate(options) {
+                    error!("{}", err);
+                    count += 1;
                 }
-                _ => {
-                    let text = std::fs::read_to_string(path)?;
-                    pages.push(text);
+            }
+            Some(Err(err)) => {
+                error!("File: {:?}. {}", path, err);
+                count += 1;
+            }
+            None => (),
+        }
+    }
+    count
+}
+
+/// Returns `true` if the `output` is a directory, because it ends with a
+/// path separator or is an existing directory. Then one presentation per
+/// notebook is written into it, like with `--separate`.
+pub fn is_output_dir(output: &str) -> bool {
+    output.ends_with(['/', std::path::MAIN_SEPARATOR]) || Path::new(output).is_dir()
+}
+
+/// Splits the `paths` into one presentation per notebook, which is written to
+/// the `output_dir` with the stem of the notebook and the given `extension`.
+/// Notebooks with the same stem get a numeric suffix. Files that are not a
+/// notebook, like a `YAML` header, are added in front of every presentation.
+pub fn separate_decks(
+    output_dir: &Path,
+    paths: &[PathBuf],
+    extension: &str,
+) -> Vec<(PathBuf, Vec<PathBuf>)> {
+    let (notebooks, files): (Vec<_>, Vec<_>) = paths
+        .iter()
+        .partition(|path| path.extension().is_some_and(|ext| ext == "ipynb"));
+
+    let mut decks: Vec<(PathBuf, Vec<PathBuf>)> = vec![];
+    for notebook in notebooks {
+        let stem = notebook
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut output_path = output_dir.join(format!("{stem}.{extension}"));
+        let mut i = 1;
+        while decks.iter().any(|(path, _)| *path == output_path) {
+            i += 1;
+            output_path = output_dir.join(format!("{stem}_{i}.{extension}"));
+        }
+
+        let mut deck = files
+            .iter()
+            .map(|file| file.to_path_buf())
+            .collect::<Vec<_>>();
+        deck.push(notebook.clone());
+        decks.push((output_path, deck));
+    }
+
+    decks
+}
+
+/// Checks if the presentation may be written to the `output_path`. Unless
+/// `force` is set, an existing file is not overridden and a symbolic link is
+/// not followed. Writing to [`STDIO`] is always allowed.
+///
+/// # Errors
+///
+/// This function will return an error if `force` is not set and the
+/// `output_path` is an existing file or a symbolic link.
+pub fn check_output_path(output_path: &Path, force: bool) -> Result<()> {
+    if force || output_path == Path::new(STDIO) {
+        return Ok(());
+    }
+
+    if fs::symlink_metadata(output_path).is_ok_and(|metadata| metadata.file_type().is_symlink()) {
+        return Err(anyhow::Error::msg(format!(
+            r#"The output path {:?} is a symbolic link. Use "-f" to write to the linked file."#,
+            output_path
+        )));
+    }
+    if output_path.is_file() && output_path.exists() {
+        return Err(anyhow::Error::msg(format!(
+            r#"File already exist {:?}. Use "-f" to force an override."#,
+            output_path
+        )));
+    }
+
+    Ok(())
+}
+
+/// Returns the text of the first level one heading in the pages. Headings
+/// inside fenced code blocks are skipped.
+fn first_heading(pages: &[String]) -> Option<String> {
+    headings(pages).next()
+}
+
+/// Returns all level one headings of the `pages` in order. Headings inside
+/// fenced code blocks are skipped.
+fn headings(pages: &[String]) -> impl Iterator<Item = String> + '_ {
+    let mut in_code_block = false;
+    pages
+        .iter()
+        .flat_map(|page| page.lines())
+        .filter_map(move |line| {
+            if line.trim().starts_with("```") {
+                in_code_block = !in_code_block;
+                None
+            } else if in_code_block {
+                None
+            } else {
+                line.strip_prefix("# ")
+                    .map(|heading| heading.trim().to_string())
+            }
+        })
+}
+
+/// Creates a table of contents page, which lists every level one heading of
+/// the `pages`, including duplicates. Returns `None` if there is no heading.
+pub fn table_of_contents(pages: &[String]) -> Option<String> {
+    let items = headings(pages)
+        .map(|heading| format!("- {heading}\n"))
+        .collect::<String>();
+    if items.is_empty() {
+        return None;
+    }
+    Some(format!("## Contents\n\n{items}"))
+}
+
+/// Returns the title of the presentation. An explicit `title` takes
+/// precedence over the first level one heading of the `pages`, which is only
+/// used if `from_first_heading` is set. Otherwise the title is the file name
+/// of the `output_path` without its extension.
+pub fn document_title(
+    title: Option<&str>,
+    from_first_heading: bool,
+    pages: &[String],
+    output_path: &Path,
+) -> String {
+    if let Some(title) = title {
+        return title.to_string();
+    }
+    if from_first_heading {
+        if let Some(heading) = first_heading(pages) {
+            return heading;
+        }
+    }
+
+    output_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Creates a title page with the given `class` from the `title`, `author` and
+/// `date`. Returns `None` if none of them is set.
+pub fn title_page(
+    title: Option<&str>,
+    author: Option<&str>,
+    date: Option<&str>,
+    class: &str,
+) -> Option<String> {
+    if title.is_none() && author.is_none() && date.is_none() {
+        return None;
+    }
+
+    let mut page = format!("class: {class}\n\n");
+    for (heading, text) in [("#", title), ("##", author), ("###", date)] {
+        if let Some(text) = text {
+            page.push_str(&format!("{heading} {text}\n"));
+        }
+    }
+    Some(page)
+}
+
+/// Returns the index of the first notebook in the `paths`, where the title
+/// page is inserted. Other files in front of it, like a `YAML` header, stay
+/// in front of the title page.
+pub fn title_page_index(paths: &[PathBuf]) -> usize {
+    paths
+        .iter()
+        .position(|path| path.extension().is_some_and(|ext| ext == "ipynb"))
+        .unwrap_or_default()
+}
+
+/// Adds the `header` at the top and the `footer` at the bottom of every slide
+/// of the `pages`, which are separated by the `separator`, after the
+/// properties and in front of the speaker notes.
+/// The paths inside the header and footer are relative to the working
+/// directory and are rewritten relative to the `output_path`, or copied to the
+/// `assets`.
+///
+/// # Errors
+///
+/// This function will return an error if the `output_path` has no parent.
+pub fn add_header_and_footer(
+    output_path: &Path,
+    assets: Option<&Path>,
+    pages: &mut [String],
+    header: Option<&str>,
+    footer: Option<&str>,
+    separator: &str,
+) -> Result<()> {
+    let rewrite = |text: &str| {
+        replace_paths(
+            output_path,
+            output_path,
+            Some(Path::new("")),
+            assets,
+            None,
+            text.trim_end().to_string(),
+        )
+        .ok_or_else(|| {
+            anyhow::Error::msg(format!("The output path {output_path:?} has no parent."))
+        })
+    };
+    let header = header.map(rewrite).transpose()?;
+    let footer = footer.map(rewrite).transpose()?;
+
+    let separator = format!("\n{separator}\n");
+    for page in pages.iter_mut().filter(|page| !page.trim().is_empty()) {
+        *page = page
+            .split(&separator)
+            .map(|slide| frame_slide(slide, header.as_deref(), footer.as_deref()))
+            .collect::<Vec<_>>()
+            .join(&separator);
+    }
+    Ok(())
+}
+
+/// Adds the number of the slide and the total number of slides, e.g. `2/10`,
+/// at the bottom of every slide of the `pages` in front of the speaker notes.
+/// The slides are separated by the `separator` and the marker is written for
+/// the `format`, see [`Format::page_number`].
+pub fn add_page_numbers(pages: &mut [String], format: Format, separator: &str) {
+    let total = split_into_slides(pages, separator).len();
+    let mut number = 0;
+    let separator = format!("\n{separator}\n");
+    for page in pages.iter_mut().filter(|page| !page.is_empty()) {
+        *page = page
+            .split(&separator)
+            .map(|slide| {
+                number += 1;
+                frame_slide(slide, None, Some(&format.page_number(number, total)))
+            })
+            .collect::<Vec<_>>()
+            .join(&separator);
+    }
+}
+
+/// Adds the `header` and `footer` to a single `slide` for
+/// [`add_header_and_footer`] and [`add_page_numbers`].
+fn frame_slide(slide: &str, header: Option<&str>, footer: Option<&str>) -> String {
+    let (_, content) = split_properties(slide);
+    let mut text = slide[..(slide.len() - content.len())].to_string();
+    let (content, notes) = match content.find("\n???\n") {
+        Some(index) => content.split_at(index + 1),
+        None => (content, ""),
+    };
+
+    if let Some(header) = header {
+        text.push_str(&format!("{header}\n"));
+    }
+    text.push_str(&format!("{}\n", content.trim_end_matches('\n')));
+    if let Some(footer) = footer {
+        text.push_str(&format!("{footer}\n"));
+    }
+    text.push_str(notes);
+    text
+}
+
+/// The base url of the reveal.js assets.
+const REVEAL_CDN: &str = "https://cdn.jsdelivr.net/npm/reveal.js@5";
+
+/// Escapes the chars of the `text` that have a special meaning in HTML.
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Creates a self-contained reveal.js HTML document from the pages. Every
+/// slide becomes a `<section>` and the tags of all slides are combined to the
+/// keywords of the document.
+fn reveal_html(pages: &[String], title: &str) -> String {
+    let mut keywords: Vec<&str> = vec![];
+    for slide in split_into_slides(pages, PAGE_SEPARATOR) {
+        let (properties, _) = split_properties(slide);
+        for (_, tags) in properties.into_iter().filter(|(name, _)| *name == "tags") {
+            for tag in tags.split(',').map(str::trim) {
+                if !tag.is_empty() && !keywords.contains(&tag) {
+                    keywords.push(tag);
                 }
             }
         }
     }
+    let keywords = if keywords.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "<meta name=\"keywords\" content=\"{}\">\n",
+            escape_html(&keywords.join(", "))
+        )
+    };
 
-    Ok(pages)
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+{keywords}<link rel="stylesheet" href="{REVEAL_CDN}/dist/reveal.css">
+<link rel="stylesheet" href="{REVEAL_CDN}/dist/theme/white.css">
+</head>
+<body>
+<div class="reveal">
+<div class="slides">
+{sections}</div>
+</div>
+<script src="{REVEAL_CDN}/dist/reveal.js"></script>
+<script src="{REVEAL_CDN}/plugin/markdown/markdown.js"></script>
+<script>
+Reveal.initialize({{ plugins: [RevealMarkdown] }});
+</script>
+</body>
+</html>
+"#,
+        title = escape_html(title),
+        sections = Format::Reveal.render(pages, title, PAGE_SEPARATOR),
+    )
 }
 
-/// Combines a list of [`String`]s representing one or multiple pages.
+/// Writes the pages as self-contained reveal.js HTML document with the given
+/// `title` to the `output_path`. The assets of reveal.js are linked from a
+/// CDN.
 ///
 /// # Errors
 ///
 /// This function will return an error if the content could not write to a file.
-pub fn write_presentation(output_path: PathBuf, pages: Vec<String>) -> Result<()> {
-    let mut file = File::create(output_path)?;
+pub fn write_reveal_html(output_path: PathBuf, pages: &[String], title: &str) -> Result<()> {
+    let mut file = create_output(&output_path)?;
+    file.write_all(reveal_html(pages, title).as_bytes())?;
+    Ok(())
+}
 
-    for page in pages {
-        if !page.is_empty() {
-            file.write_all(b"\n---\n\n")?;
-            file.write_all(page.as_bytes())?
+/// Combines a list of [`String`]s representing one or multiple pages and
+/// writes them in the given `format` with the given `title`. The
+/// `front_matter` is written at the top of the presentation, in front of the
+/// first page. The slides of the pages are separated by the `separator`,
+/// which is also written between the pages of a remark presentation. The
+/// output path [`STDIO`] writes to `stdout`.
+///
+/// # Errors
+///
+/// This function will return an error if the content could not write to a file.
+pub fn write_presentation(
+    output_path: PathBuf,
+    pages: Vec<String>,
+    format: Format,
+    title: &str,
+    front_matter: Option<&str>,
+    separator: &str,
+) -> Result<()> {
+    if pages.iter().all(|page| page.trim().is_empty()) {
+        warn!("The presentation {:?} has no content.", output_path);
+    }
+    if format == Format::Reveal {
+        if front_matter.is_some() {
+            warn!("The front matter is not supported by the format {format}.");
         }
+        return write_reveal_html(output_path, &pages, title);
+    }
+
+    let mut text = format.render(&pages, title, separator);
+    if let Some(front_matter) = front_matter {
+        text = format.add_front_matter(&text, front_matter);
     }
+    let mut file = create_output(&output_path)?;
+    file.write_all(text.as_bytes())?;
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use std::{
+        path::{Path, PathBuf},
+        time::{Duration, SystemTime},
+    };
+
+    use crate::{
+        format::{split_into_slides, Format, PAGE_SEPARATOR},
+        notebook::NotebookOptions,
+    };
+
+    use super::{
+        add_header_and_footer, add_page_numbers, check_output_path, collect_pages, document_title,
+        is_output_dir, read_file, read_page, reveal_html, separate_decks, stream_presentation,
+        table_of_contents, title_page, title_page_index, write_presentation, PageCache, PageIds,
+    };
+
+    #[test]
+    fn test_collect_pages_reversed() {
+        let output_path = PathBuf::from("tests/presentations/output.rmd");
+        let mut paths = vec![
+            PathBuf::from("tests/notebooks/multiple_books/01_book.ipynb"),
+            PathBuf::from("tests/notebooks/multiple_books/02_book.ipynb"),
+        ];
+
+        let mut pages = collect_pages(
+            output_path.clone(),
+            &NotebookOptions::default(),
+            &paths,
+            None,
+        )
+        .unwrap()
+        .0;
+        paths.reverse();
+        let reversed_pages = collect_pages(output_path, &NotebookOptions::default(), &paths, None)
+            .unwrap()
+            .0;
+
+        assert_eq!(pages.len(), 2);
+        assert!(reversed_pages[0].contains("This is the second page"));
+        pages.reverse();
+        assert_eq!(reversed_pages, pages);
+    }
+
+    #[test]
+    fn test_collect_pages_with_invalid_notebook() {
+        let dir = tempfile::tempdir().unwrap();
+        let invalid = dir.path().join("invalid.ipynb");
+        std::fs::write(&invalid, "{ not json").unwrap();
+        let output_path = PathBuf::from("tests/presentations/output.rmd");
+        let paths = vec![
+            invalid.clone(),
+            PathBuf::from("tests/notebooks/multiple_books/01_book.ipynb"),
+        ];
+
+        let pages = collect_pages(
+            output_path.clone(),
+            &NotebookOptions::default(),
+            &paths,
+            None,
+        )
+        .unwrap()
+        .0;
+        assert_eq!(pages[0], "");
+        assert!(pages[1].contains("This is the first page"));
+
+        let strict = NotebookOptions {
+            strict: true,
+            ..Default::default()
+        };
+        assert!(collect_pages(output_path.clone(), &strict, &paths, None).is_err());
+        assert!(collect_pages(output_path, &NotebookOptions::default(), &[invalid], None).is_err());
+    }
+
+    #[test]
+    fn test_collect_pages_with_import() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("decks")).unwrap();
+        let master = dir.path().join("master.md");
+        std::fs::write(
+            &master,
+            "# Master\n---\n\n@import decks/child.rmd\n---\n\n# End\n```\n@import missing.rmd\n```\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("decks/child.rmd"),
+            "# Child 1\n---\n\n@import grandchild.rmd\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("decks/grandchild.rmd"), "# Child 2\n").unwrap();
+
+        let pages = collect_pages(
+            dir.path().join("output.rmd"),
+            &NotebookOptions::default(),
+            std::slice::from_ref(&master),
+            None,
+        )
+        .unwrap()
+        .0;
+        assert_eq!(
+            split_into_slides(&pages, PAGE_SEPARATOR),
+            vec![
+                "# Master",
+                "\n# Child 1",
+                "\n# Child 2",
+                "\n# End\n```\n@import missing.rmd\n```\n"
+            ]
+        );
+
+        std::fs::write(
+            dir.path().join("decks/grandchild.rmd"),
+            "@import ../master.md",
+        )
+        .unwrap();
+        let err = read_file(&master, &mut vec![]).unwrap_err();
+        assert!(err.to_string().contains("is imported by itself"));
+    }
+
+    #[test]
+    fn test_collect_pages_without_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let empty = dir.path().join("empty.ipynb");
+        std::fs::write(&empty, r#"{"cells": []}"#).unwrap();
+        let code = dir.path().join("code.ipynb");
+        std::fs::write(
+            &code,
+            r#"{"cells": [{"cell_type": "code", "metadata": {}, "outputs": [], "source": ["x = 1"]}]}"#,
+        )
+        .unwrap();
+        let output_path = dir.path().join("output.rmd");
+
+        let pages = collect_pages(
+            output_path.clone(),
+            &NotebookOptions::default(),
+            &[empty, code],
+            None,
+        )
+        .unwrap()
+        .0;
+        assert_eq!(pages, vec![String::new(), String::new()]);
+
+        write_presentation(
+            output_path.clone(),
+            pages,
+            Format::Remark,
+            "Title",
+            None,
+            PAGE_SEPARATOR,
+        )
+        .unwrap();
+        assert_eq!(std::fs::read_to_string(output_path).unwrap(), "");
+    }
+
+    #[test]
+    fn test_write_presentation_without_leading_separator() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_path = dir.path().join("output.rmd");
+        let pages = vec![
+            "# First\n".to_string(),
+            String::new(),
+            "# Second\n".to_string(),
+        ];
+
+        write_presentation(
+            output_path.clone(),
+            pages,
+            Format::Remark,
+            "Title",
+            None,
+            PAGE_SEPARATOR,
+        )
+        .unwrap();
+        let bytes = std::fs::read(output_path).unwrap();
+        assert!(!bytes.starts_with(b"---"));
+        assert!(!bytes.starts_with(b"\n"));
+        assert_eq!(bytes, b"# First\n\n---\n\n# Second\n");
+    }
+
+    #[test]
+    fn test_write_presentation_with_separator() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_path = dir.path().join("output.rmd");
+        let pages = vec![
+            "# First\n***\n\n# Second\n".to_string(),
+            "# Third\n---\n".to_string(),
+        ];
+
+        write_presentation(
+            output_path.clone(),
+            pages,
+            Format::Remark,
+            "Title",
+            Some("---\nlayout: true\n---"),
+            "***",
+        )
+        .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(output_path).unwrap(),
+            "---\nlayout: true\n---\n\n# First\n***\n\n# Second\n\n***\n\n# Third\n---\n"
+        );
+    }
+
+    #[test]
+    fn test_write_presentation_with_front_matter() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_path = dir.path().join("output.rmd");
+        let pages = vec!["# First\n".to_string(), "# Second\n".to_string()];
+        let front_matter = "---\ntheme: default\npaginate: true\n---\n";
+
+        write_presentation(
+            output_path.clone(),
+            pages,
+            Format::Remark,
+            "Title",
+            Some(front_matter),
+            PAGE_SEPARATOR,
+        )
+        .unwrap();
+        let text = std::fs::read_to_string(output_path).unwrap();
+        assert!(text.starts_with(&format!("{front_matter}\n# First\n")));
+        assert_eq!(text.matches("\n---\n").count(), 2);
+    }
+
+    #[test]
+    fn test_page_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let notebook = |source: &str| {
+            format!(
+                r#"{{"cells": [{{"cell_type": "markdown", "metadata": {{}}, "source": [{}]}}]}}"#,
+                serde_json::to_string(source).unwrap()
+            )
+        };
+        let touch = |path: &Path, content: &str, secs: u64| {
+            std::fs::write(path, content).unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs))
+                .unwrap();
+        };
+        let first = dir.path().join("first.ipynb");
+        let second = dir.path().join("second.ipynb");
+        let snippet = dir.path().join("snippet.md");
+        touch(&first, &notebook("<!--! new; include[snippet.md] -->"), 1);
+        touch(&second, &notebook("<!--! new; inject[# Second] -->"), 1);
+        touch(&snippet, "# Snippet\n", 1);
+        let output_path = dir.path().join("output.rmd");
+        let paths = vec![first, second.clone()];
+        let options = NotebookOptions::default();
+        let cache = PageCache::default();
+
+        let pages = collect_pages(output_path.clone(), &options, &paths, Some(&cache))
+            .unwrap()
+            .0;
+        assert_eq!(cache.conversions(), 2);
+        assert_eq!(pages, vec!["# Snippet\n", "# Second"]);
+
+        touch(&second, &notebook("<!--! new; inject[# Changed] -->"), 2);
+        let pages = collect_pages(output_path.clone(), &options, &paths, Some(&cache))
+            .unwrap()
+            .0;
+        assert_eq!(cache.conversions(), 3);
+        assert_eq!(pages, vec!["# Snippet\n", "# Changed"]);
+
+        touch(&snippet, "# Included\n", 2);
+        let pages = collect_pages(output_path.clone(), &options, &paths, Some(&cache))
+            .unwrap()
+            .0;
+        assert_eq!(cache.conversions(), 4);
+        assert_eq!(pages, vec!["# Included\n", "# Changed"]);
+
+        let (_, notebook_pages) =
+            collect_pages(output_path, &options, &paths, Some(&cache)).unwrap();
+        assert_eq!(cache.conversions(), 4);
+        assert_eq!(notebook_pages[1][0].cell, Some(0));
+    }
+
+    #[test]
+    fn test_stream_presentation() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = vec![
+            PathBuf::from("tests/notebooks/class.ipynb"),
+            PathBuf::from("tests/notebooks/multiple_books/01_book.ipynb"),
+            PathBuf::from("tests/head_page.rmd"),
+            PathBuf::from("tests/notebooks/multiple_books/02_book.ipynb"),
+            PathBuf::from("tests/notebooks/wrap_images.ipynb"),
+        ];
+        let front_matter = "layout: true\n";
+
+        for (front_matter, separator) in [(None, PAGE_SEPARATOR), (Some(front_matter), "***")] {
+            let options = NotebookOptions {
+                separator: Some(separator.to_string()),
+                ..Default::default()
+            };
+            let buffered = dir.path().join("buffered.rmd");
+            let pages = collect_pages(buffered.clone(), &options, &paths, None)
+                .unwrap()
+                .0;
+            write_presentation(
+                buffered.clone(),
+                pages,
+                Format::Remark,
+                "Title",
+                front_matter,
+                separator,
+            )
+            .unwrap();
+            let streamed = dir.path().join("streamed.rmd");
+            stream_presentation(&streamed, &options, &paths, None, "Title", front_matter).unwrap();
+
+            assert_eq!(
+                std::fs::read(streamed).unwrap(),
+                std::fs::read(buffered).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_collect_pages_in_parallel() {
+        let output_path = PathBuf::from("tests/presentations/output.rmd");
+        let options = NotebookOptions::default();
+        let paths = vec![
+            PathBuf::from("tests/notebooks/class.ipynb"),
+            PathBuf::from("tests/notebooks/multiple_books/01_book.ipynb"),
+            PathBuf::from("tests/head_page.rmd"),
+            PathBuf::from("tests/notebooks/multiple_books/02_book.ipynb"),
+            PathBuf::from("tests/notebooks/wrap_images.ipynb"),
+        ];
+
+        let sequential = paths
+            .iter()
+            .map(|path| read_page(&output_path, &options, path).unwrap())
+            .collect::<Vec<_>>();
+        let parallel = collect_pages(output_path, &options, &paths, None)
+            .unwrap()
+            .0;
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn test_document_title() {
+        let output_path = PathBuf::from("presentations/fire_dynamics.rmd");
+        let pages = vec![
+            "```python\n# comment\n```\n## Subheading".to_string(),
+            "# Combustion\n---\n\n# Plumes".to_string(),
+        ];
+
+        let title = document_title(Some("Explicit"), true, &pages, &output_path);
+        assert_eq!(title, "Explicit");
+        let title = document_title(None, true, &pages, &output_path);
+        assert_eq!(title, "Combustion");
+        let title = document_title(None, false, &pages, &output_path);
+        assert_eq!(title, "fire_dynamics");
+        let title = document_title(None, true, &[], &output_path);
+        assert_eq!(title, "fire_dynamics");
+    }
+
+    #[test]
+    fn test_table_of_contents() {
+        let pages = vec![
+            "# Introduction\nText\n\n---\n\n# Plumes\n".to_string(),
+            "```python\n# a comment\n```\n## Subheading\n".to_string(),
+            "#Not a heading\n# Plumes\n".to_string(),
+        ];
+        assert_eq!(
+            table_of_contents(&pages),
+            Some("## Contents\n\n- Introduction\n- Plumes\n- Plumes\n".to_string())
+        );
+        assert_eq!(table_of_contents(&["Text\n".to_string()]), None);
+    }
+
+    #[test]
+    fn test_title_page() {
+        assert_eq!(title_page(None, None, None, "center, title"), None);
+
+        let page = title_page(Some("Fire Dynamics"), None, Some("2023"), "center, title");
+        assert_eq!(
+            page,
+            Some("class: center, title\n\n# Fire Dynamics\n### 2023\n".to_string())
+        );
+
+        let mut paths = vec![
+            PathBuf::from("tests/head_page.rmd"),
+            PathBuf::from("tests/notebooks/multiple_books/01_book.ipynb"),
+            PathBuf::from("tests/notebooks/multiple_books/02_book.ipynb"),
+        ];
+        let mut pages = collect_pages(
+            PathBuf::from("tests/presentations/output.rmd"),
+            &NotebookOptions::default(),
+            &paths,
+            None,
+        )
+        .unwrap()
+        .0;
+        let index = title_page_index(&paths);
+        pages.insert(index, page.unwrap());
+        paths.insert(index, PathBuf::from("tests/presentations/output.rmd"));
+
+        assert_eq!(index, 1);
+        assert!(pages[0].starts_with("---\ntitle: \"Test\""));
+        assert!(pages[1].contains("# Fire Dynamics"));
+        assert!(pages[2].contains("This is the first page"));
+        assert!(pages[3].contains("This is the second page"));
+    }
+
+    #[test]
+    fn test_duplicate_ids() {
+        let pages = vec![
+            "name: intro\n\n# Intro\n---\n\nname: fire\n\n# Fire".to_string(),
+            "name: intro\n\ntags: fire\n\n# Other\n---\n\nname: intro\n\n# Third".to_string(),
+            "# Without id".to_string(),
+        ];
+        let duplicate_ids = |pages: &[String]| {
+            let mut ids = PageIds::default();
+            for page in pages {
+                ids.add(page, PAGE_SEPARATOR);
+            }
+            ids.duplicates
+        };
+        assert_eq!(duplicate_ids(&pages), vec!["intro"]);
+        assert!(duplicate_ids(&pages[..1]).is_empty());
+    }
+
+    #[test]
+    fn test_add_header_and_footer() {
+        let mut pages = vec![
+            "class: center, title\n\n# Title\n".to_string(),
+            String::new(),
+            "# First\n---\n\nname: second\n\n# Second\n???\nNote\n".to_string(),
+        ];
+        add_header_and_footer(
+            Path::new("slides/output.rmd"),
+            None,
+            &mut pages,
+            Some("Fire Dynamics"),
+            Some("![Logo](images/logo.png) Talk"),
+            PAGE_SEPARATOR,
+        )
+        .unwrap();
+
+        let footer = "![Logo](../images/logo.png) Talk";
+        assert_eq!(
+            pages,
+            vec![
+                format!("class: center, title\n\nFire Dynamics\n# Title\n{footer}\n"),
+                String::new(),
+                format!(
+                    "Fire Dynamics\n# First\n{footer}\n\n---\n\nname: second\n\nFire Dynamics\n# Second\n{footer}\n???\nNote\n"
+                ),
+            ]
+        );
+        let slides = split_into_slides(&pages, PAGE_SEPARATOR);
+        assert_eq!(slides.len(), 3);
+        assert!(slides
+            .iter()
+            .all(|slide| slide.matches(footer).count() == 1));
+    }
+
+    #[test]
+    fn test_add_page_numbers() {
+        let mut pages = vec![
+            "class: center, title\n\n# Title\n".to_string(),
+            "# First\n---\n\nname: second\n\n# Second\n???\nNote\n".to_string(),
+            String::new(),
+            "# Third".to_string(),
+        ];
+        add_page_numbers(&mut pages[1..], Format::Remark, PAGE_SEPARATOR);
+        assert_eq!(
+            pages,
+            vec![
+                "class: center, title\n\n# Title\n".to_string(),
+                "# First\n.page-number[1/3]\n\n---\n\nname: second\n\n# Second\n.page-number[2/3]\n???\nNote\n"
+                    .to_string(),
+                String::new(),
+                "# Third\n.page-number[3/3]\n".to_string(),
+            ]
+        );
+
+        let mut pages = vec!["# Title".to_string(), "# First".to_string()];
+        add_page_numbers(&mut pages, Format::Marp, PAGE_SEPARATOR);
+        assert_eq!(
+            pages,
+            vec![
+                "# Title\n<div class=\"page-number\">1/2</div>\n",
+                "# First\n<div class=\"page-number\">2/2</div>\n"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_output_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(is_output_dir(dir.path().to_str().unwrap()));
+        assert!(is_output_dir("presentations/"));
+        assert!(!is_output_dir("presentations/output.rmd"));
+        assert!(!is_output_dir("-"));
+    }
+
+    #[test]
+    fn test_separate_decks() {
+        let head_page = PathBuf::from("tests/head_page.rmd");
+        let first = PathBuf::from("tests/notebooks/multiple_books/01_book.ipynb");
+        let second = PathBuf::from("tests/notebooks/01_book.ipynb");
+        let paths = vec![head_page.clone(), first.clone(), second.clone()];
+
+        let decks = separate_decks(Path::new("tests/presentations"), &paths, "rmd");
+        assert_eq!(
+            decks,
+            vec![
+                (
+                    PathBuf::from("tests/presentations/01_book.rmd"),
+                    vec![head_page.clone(), first]
+                ),
+                (
+                    PathBuf::from("tests/presentations/01_book_2.rmd"),
+                    vec![head_page, second]
+                ),
+            ]
+        );
+
+        let notebook = PathBuf::from("tests/notebooks/wrap_images.ipynb");
+        let decks = separate_decks(Path::new("tests/presentations/weekly"), &[notebook], "rmd");
+        let pages = collect_pages(
+            decks[0].0.clone(),
+            &NotebookOptions::default(),
+            &decks[0].1,
+            None,
+        )
+        .unwrap()
+        .0;
+        assert!(pages[0].contains("](../../../tests/notebooks/./../images/image1.png)"));
+    }
+
+    #[test]
+    fn test_reveal_html() {
+        let pages = vec![
+            "tags: fire, plume\n\n# First".to_string(),
+            "class: center\n\n# Second".to_string(),
+            "tags: plume, smoke\n\n# Third".to_string(),
+        ];
+
+        let html = reveal_html(&pages, "Fire & Smoke");
+        assert_eq!(html.matches("<section").count(), pages.len());
+        assert!(html.contains("<title>Fire &amp; Smoke</title>"));
+        assert!(html.contains(r#"<meta name="keywords" content="fire, plume, smoke">"#));
+        assert!(html.contains(r#"<section data-markdown data-tags="fire, plume">"#));
+        assert!(html.contains(r#"<section data-markdown class="center">"#));
+    }
+
+    #[test]
+    fn test_check_output_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_path = dir.path().join("output.rmd");
+
+        assert!(check_output_path(&output_path, false).is_ok());
+        std::fs::write(&output_path, "").unwrap();
+        assert!(check_output_path(&output_path, false).is_err());
+        assert!(check_output_path(&output_path, true).is_ok());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_output_path_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let target_path = dir.path().join("target.rmd");
+        let output_path = dir.path().join("output.rmd");
+        std::os::unix::fs::symlink(&target_path, &output_path).unwrap();
+
+        let err = check_output_path(&output_path, false).unwrap_err();
+        assert!(err.to_string().contains("is a symbolic link"));
+        assert!(check_output_path(&output_path, true).is_ok());
+    }
+}
@@ -0,0 +1,193 @@
+//! Describes the structure of the generated presentation, with the source
+//! notebook and cell of every slide. The description is written as `json`
+//! and can be read by other tools, e.g. a learning management system.
+use anyhow::Result;
+use serde::Serialize;
+use std::{
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    format::split_properties,
+    notebook::{NotebookOptions, Page},
+};
+
+/// The description of a single slide of the presentation.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SlideEntry {
+    /// The number of the slide, starting at `1`.
+    pub slide: usize,
+    /// The notebook or file the slide was created from, or `None` for the
+    /// generated title page and table of contents.
+    pub source: Option<PathBuf>,
+    /// The index of the cell of the notebook that started the slide.
+    pub cell: Option<usize>,
+    /// The class of the slide, e.g. `center, middle`.
+    pub class: Option<String>,
+    /// The id of the slide, which is written as `name` property.
+    pub id: Option<String>,
+}
+
+/// Creates the description of every slide of the `pages`, which were created
+/// from the notebooks and files at the `paths` of the same index with the
+/// `options`. The slides of a notebook are described by its [`Page`]s in
+/// the `notebook_pages` of the same index, see
+/// [`collect_pages`](crate::create_presentation::collect_pages). The slides
+/// of other files and of the pages of the `output_path`, which are generated
+/// like the title page, are read from their properties.
+pub fn create_deck_manifest(
+    output_path: &Path,
+    options: &NotebookOptions,
+    paths: &[PathBuf],
+    pages: &[String],
+    notebook_pages: &[Vec<Page>],
+) -> Vec<SlideEntry> {
+    let separator = format!("\n{}\n", options.separator());
+    let mut entries = vec![];
+    for ((path, page), notebook_pages) in paths.iter().zip(pages).zip(notebook_pages) {
+        if page.is_empty() {
+            continue;
+        }
+        let generated = path == output_path;
+        let source = (!generated).then(|| path.clone());
+
+        if !notebook_pages.is_empty() {
+            for page in notebook_pages {
+                entries.push(SlideEntry {
+                    slide: entries.len() + 1,
+                    source: source.clone(),
+                    cell: page.cell,
+                    class: page.class.clone(),
+                    id: page.id.clone(),
+                });
+                // A separator in the markdown of the page starts further
+                // slides, which belong to the same cells.
+                for _ in page.body.matches(&separator) {
+                    entries.push(SlideEntry {
+                        slide: entries.len() + 1,
+                        source: source.clone(),
+                        cell: page.cell,
+                        class: None,
+                        id: None,
+                    });
+                }
+            }
+            continue;
+        }
+
+        for slide in page.split(&separator) {
+            let (properties, _) = split_properties(slide);
+            let property = |name: &str| {
+                properties
+                    .iter()
+                    .find(|(property, _)| *property == name)
+                    .map(|(_, value)| value.to_string())
+            };
+            entries.push(SlideEntry {
+                slide: entries.len() + 1,
+                source: source.clone(),
+                cell: None,
+                class: property("class"),
+                id: property("name"),
+            });
+        }
+    }
+    entries
+}
+
+/// Creates the description of the slides, see [`create_deck_manifest`], and
+/// writes it as `json` to the `manifest_path`.
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be created or
+/// the description could not be written to it.
+pub fn write_deck_manifest(
+    manifest_path: PathBuf,
+    output_path: &Path,
+    options: &NotebookOptions,
+    paths: &[PathBuf],
+    pages: &[String],
+    notebook_pages: &[Vec<Page>],
+) -> Result<()> {
+    let file = File::create(manifest_path)?;
+    serde_json::to_writer_pretty(
+        file,
+        &create_deck_manifest(output_path, options, paths, pages, notebook_pages),
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::{
+        create_presentation::{collect_pages, title_page},
+        format::split_into_slides,
+        notebook::NotebookOptions,
+    };
+
+    use super::create_deck_manifest;
+
+    #[test]
+    fn test_create_deck_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let split = dir.path().join("split.ipynb");
+        std::fs::write(
+            &split,
+            r##"{"cells": [
+                {"cell_type": "markdown", "metadata": {}, "source": ["<!--! new; start-add -->\n", "# First\n", "\n", "---\n", "\n", "# Second"]},
+                {"cell_type": "markdown", "metadata": {}, "source": ["<!--! new; start-add; class[center] -->\n", "# Third"]}
+            ]}"##,
+        )
+        .unwrap();
+        let output_path = PathBuf::from("tests/presentations/output.rmd");
+        let options = NotebookOptions::default();
+        let class = PathBuf::from("tests/notebooks/class.ipynb");
+        let book = PathBuf::from("tests/notebooks/multiple_books/01_book.ipynb");
+        let mut paths = vec![
+            PathBuf::from("tests/head_page.rmd"),
+            class.clone(),
+            book.clone(),
+            split.clone(),
+        ];
+        let (mut pages, mut notebook_pages) =
+            collect_pages(output_path.clone(), &options, &paths, None).unwrap();
+        pages.insert(1, title_page(Some("Title"), None, None, "center").unwrap());
+        paths.insert(1, output_path.clone());
+        notebook_pages.insert(1, vec![]);
+
+        let entries = create_deck_manifest(&output_path, &options, &paths, &pages, &notebook_pages);
+        assert_eq!(
+            entries.len(),
+            split_into_slides(&pages, options.separator()).len()
+        );
+        for (i, entry) in entries.iter().enumerate() {
+            assert_eq!(entry.slide, i + 1);
+        }
+        assert_eq!(entries[1].source, None);
+        assert_eq!(entries[1].class.as_deref(), Some("center"));
+
+        let cells = |path: &PathBuf| {
+            entries
+                .iter()
+                .filter(|entry| entry.source.as_ref() == Some(path))
+                .map(|entry| entry.cell)
+                .collect::<Vec<_>>()
+        };
+        assert!(cells(&paths[0]).iter().all(Option::is_none));
+        assert_eq!(cells(&class), vec![Some(0), Some(1), Some(2)]);
+        assert_eq!(cells(&book), vec![Some(0)]);
+        assert_eq!(cells(&split), vec![Some(0), Some(0), Some(1)]);
+
+        let classes = entries
+            .iter()
+            .filter(|entry| entry.source.as_ref() == Some(&class))
+            .map(|entry| entry.class.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(classes, vec![Some("topic"), Some("topic"), Some("rotate")]);
+        assert_eq!(entries.last().unwrap().class.as_deref(), Some("center"));
+    }
+}
@@ -0,0 +1,731 @@
+//! The output formats of the presentation. Every format renders the collected
+//! pages into the text of the presentation file.
+use anyhow::Result;
+use std::{fmt::Display, str::FromStr};
+
+use crate::notebook::TITLE_VARIABLE;
+
+/// The renderer that is used to write the presentation.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Markdown for remark.js, where the pages are separated by `---`.
+    #[default]
+    Remark,
+    /// Markdown for Marp with a front matter block enabling Marp.
+    Marp,
+    /// A reveal.js HTML document, where every slide is a `<section>`. Only the
+    /// sections are rendered, the document is created by
+    /// [`crate::create_presentation::write_reveal_html`].
+    Reveal,
+    /// A LaTeX beamer document, where every slide is a `frame`.
+    Beamer,
+}
+
+impl Format {
+    /// The names of all formats, as they are passed as argument.
+    pub const NAMES: &'static [&'static str] = &["remark", "marp", "reveal", "beamer"];
+
+    /// Returns the file extension of a presentation in this format.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Remark => "rmd",
+            Format::Marp => "md",
+            Format::Reveal => "html",
+            Format::Beamer => "tex",
+        }
+    }
+
+    /// Returns the marker of the slide `number` out of the `total` slides,
+    /// e.g. `.page-number[2/10]` for remark, which is added at the bottom of
+    /// the slide.
+    pub fn page_number(&self, number: usize, total: usize) -> String {
+        match self {
+            Format::Remark => format!(".page-number[{number}/{total}]"),
+            Format::Marp | Format::Reveal => {
+                format!(r#"<div class="page-number">{number}/{total}</div>"#)
+            }
+            Format::Beamer => format!("{number}/{total}"),
+        }
+    }
+
+    /// Renders the `pages` into the text of the presentation with the given
+    /// `title`. The slides of the pages are separated by the `separator`,
+    /// which is also written between the pages of a remark presentation.
+    pub fn render(&self, pages: &[String], title: &str, separator: &str) -> String {
+        let pages = pages
+            .iter()
+            .map(|page| normalize_math(page, *self))
+            .collect::<Vec<_>>();
+        let pages = pages.as_slice();
+        let text = match self {
+            Format::Remark => pages
+                .iter()
+                .filter(|page| !page.is_empty())
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(&format!("\n{separator}\n\n")),
+            Format::Marp => {
+                // The separators need a blank line in front, otherwise the
+                // line before becomes a heading.
+                let slides = split_into_slides(pages, separator)
+                    .into_iter()
+                    .map(|slide| marp_slide(slide).trim_end_matches('\n').to_string())
+                    .collect::<Vec<_>>();
+                format!(
+                    "---\nmarp: true\ntitle: \"{}\"\n---\n\n{}\n",
+                    title.replace('\\', "\\\\").replace('"', "\\\""),
+                    slides.join("\n\n---\n\n")
+                )
+            }
+            Format::Reveal => split_into_slides(pages, separator)
+                .into_iter()
+                .map(reveal_section)
+                .collect(),
+            Format::Beamer => {
+                let frames = split_into_slides(pages, separator)
+                    .into_iter()
+                    .map(beamer_frame)
+                    .collect::<String>();
+                format!(
+                    "\\documentclass{{beamer}}\n\\usepackage{{graphicx}}\n\\title{{{}}}\n\
+                     \\begin{{document}}\n{frames}\\end{{document}}\n",
+                    escape_latex(title)
+                )
+            }
+        };
+
+        text.replace(&format!("{{{{{TITLE_VARIABLE}}}}}"), title)
+    }
+
+    /// Adds the `front_matter` in front of the rendered `text` of the
+    /// presentation, separated by a blank line from the first page. For Marp
+    /// the keys of the front matter are merged into the generated front
+    /// matter block instead, where they override the generated keys, and for
+    /// beamer it is added to the preamble.
+    pub fn add_front_matter(&self, text: &str, front_matter: &str) -> String {
+        if *self == Format::Beamer {
+            let front_matter = front_matter.trim_end_matches('\n');
+            return text.replacen(
+                "\\begin{document}",
+                &format!("{front_matter}\n\\begin{{document}}"),
+                1,
+            );
+        }
+        if *self != Format::Marp {
+            let front_matter = front_matter.trim_end_matches('\n');
+            return format!("{front_matter}\n\n{text}");
+        }
+
+        /// Returns the key of a `YAML` line.
+        fn key(line: &str) -> &str {
+            line.split_once(':').map_or(line, |(key, _)| key).trim()
+        }
+        let Some((header, slides)) = text
+            .strip_prefix("---\n")
+            .and_then(|text| text.split_once("\n---\n"))
+        else {
+            return text.to_string();
+        };
+        let front_matter = front_matter.trim();
+        let front_matter = front_matter
+            .strip_prefix("---")
+            .unwrap_or(front_matter)
+            .trim_end_matches("---")
+            .trim();
+        let keys = front_matter.lines().map(key).collect::<Vec<_>>();
+        let header = header
+            .lines()
+            .filter(|line| !keys.contains(&key(line)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        format!("---\n{header}\n{front_matter}\n---\n{slides}")
+    }
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "remark" => Ok(Format::Remark),
+            "marp" => Ok(Format::Marp),
+            "reveal" => Ok(Format::Reveal),
+            "beamer" => Ok(Format::Beamer),
+            _ => Err(anyhow::Error::msg(format!(
+                "Unknown format '{s}'. Use one of {}.",
+                Format::NAMES.join(", ")
+            ))),
+        }
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Format::Remark => Format::NAMES[0],
+            Format::Marp => Format::NAMES[1],
+            Format::Reveal => Format::NAMES[2],
+            Format::Beamer => Format::NAMES[3],
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Splits the pages of the presentation into single slides. Every page can
+/// hold multiple slides separated by the `separator`, e.g. `---`.
+pub fn split_into_slides<'a>(pages: &'a [String], separator: &str) -> Vec<&'a str> {
+    let separator = format!("\n{separator}\n");
+    pages
+        .iter()
+        .filter(|page| !page.is_empty())
+        .flat_map(|page| page.split(separator.as_str()))
+        .collect()
+}
+
+/// Splits the remark.js properties (e.g. `class: center`) at the start of a
+/// slide from its content. Only the properties `class`, `tags` and `name` are
+/// recognized.
+pub fn split_properties(slide: &str) -> (Vec<(&str, &str)>, &str) {
+    let mut properties = vec![];
+    let mut rest = slide.trim_start_matches('\n');
+
+    loop {
+        let (line, remaining) = rest.split_once('\n').unwrap_or((rest, ""));
+        match line.split_once(':') {
+            Some((name @ ("class" | "tags" | "name"), value)) => {
+                properties.push((name, value.trim()));
+                rest = remaining.trim_start_matches('\n');
+            }
+            _ => break,
+        }
+    }
+
+    (properties, rest)
+}
+
+/// Replaces the math delimiters `\(...\)` of the `line` with `$...$` and a
+/// `\[...\]` block on its own lines with `$$...$$`, because markdown removes
+/// the backslashes. Inline code is kept.
+fn convert_math_delimiters(line: &str) -> String {
+    let trimmed = line.trim();
+    if trimmed == "\\[" || trimmed == "\\]" {
+        return line.replacen(trimmed, "$$", 1);
+    }
+    if let Some(math) = trimmed
+        .strip_prefix("\\[")
+        .and_then(|math| math.strip_suffix("\\]"))
+    {
+        return line.replacen(trimmed, &format!("$${math}$$"), 1);
+    }
+
+    line.split('`')
+        .enumerate()
+        .map(|(i, part)| {
+            if i % 2 == 1 {
+                return part.to_string();
+            }
+            let mut text = String::new();
+            let mut rest = part;
+            while let Some((start, end)) = rest
+                .find("\\(")
+                .and_then(|start| Some((start, start + rest[start..].find("\\)")?)))
+            {
+                text.push_str(&format!("{}${}$", &rest[..start], &rest[(start + 2)..end]));
+                rest = &rest[(end + 2)..];
+            }
+            text.push_str(rest);
+            text
+        })
+        .collect::<Vec<_>>()
+        .join("`")
+}
+
+/// Moves the display math `$$...$$` inside the `line` to its own line, which
+/// remark.js requires. Lines with inline code are kept.
+fn split_display_math(line: &str) -> String {
+    let parts = line.split("$$").collect::<Vec<_>>();
+    let is_block = parts.len() == 3 && parts[0].trim().is_empty() && parts[2].trim().is_empty();
+    if parts.len() < 3 || parts.len() % 2 == 0 || is_block || line.contains('`') {
+        return line.to_string();
+    }
+
+    parts
+        .iter()
+        .enumerate()
+        .filter_map(|(i, part)| match i {
+            i if i % 2 == 1 => Some(format!("$${part}$$")),
+            0 => Some(part.trim_end().to_string()).filter(|part| !part.is_empty()),
+            _ => Some(part.trim().to_string()).filter(|part| !part.is_empty()),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The separator of the slides inside the program, which is also written
+/// between the slides of a remark presentation by default.
+pub const PAGE_SEPARATOR: &str = "---";
+
+/// Checks the custom page `separator` of a remark presentation, e.g. `***`
+/// or `<!-- slide -->`. Returns the trimmed separator.
+///
+/// # Errors
+///
+/// This function will return an error if the separator is empty or spans
+/// multiple lines, since it has to be a line of its own, or if the line
+/// already has a meaning in remark or markdown, see [`separator_collision`].
+pub fn check_separator(separator: &str) -> Result<String> {
+    let separator = separator.trim();
+    if separator.is_empty() || separator.contains('\n') {
+        return Err(anyhow::Error::msg(format!(
+            "The separator '{separator}' has to be a single, non-empty line."
+        )));
+    }
+    if let Some(collision) = separator_collision(separator) {
+        return Err(anyhow::Error::msg(format!(
+            "The separator '{separator}' can not be used, since the line is {collision}."
+        )));
+    }
+    Ok(separator.to_string())
+}
+
+/// Returns what the `separator` line already means in a remark presentation,
+/// e.g. the `--` of an incremental slide, or in markdown, e.g. a heading.
+/// Returns `None` if the line can be used as separator.
+fn separator_collision(separator: &str) -> Option<&'static str> {
+    let ordered_item = separator
+        .trim_start_matches(|c: char| c.is_ascii_digit())
+        .strip_prefix(['.', ')'])
+        .filter(|_| separator.starts_with(|c: char| c.is_ascii_digit()));
+    let property = separator.split_once(':').is_some_and(|(name, _)| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    });
+
+    if separator == "--" {
+        Some("the incremental slide of remark")
+    } else if separator == "???" {
+        Some("the start of the notes of remark")
+    } else if property {
+        Some("a property of a remark slide")
+    } else if separator.starts_with('#') {
+        Some("a markdown heading")
+    } else if separator.chars().all(|c| c == '=') {
+        Some("the underline of a markdown heading")
+    } else if separator.starts_with('>') {
+        Some("a markdown quote")
+    } else if separator.starts_with("```") || separator.starts_with("~~~") {
+        Some("a markdown code fence")
+    } else if separator.starts_with('|') {
+        Some("a markdown table")
+    } else if ["- ", "* ", "+ "]
+        .iter()
+        .any(|marker| separator.starts_with(marker))
+        || matches!(separator, "-" | "*" | "+")
+        || ordered_item.is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+    {
+        Some("a markdown list item")
+    } else {
+        None
+    }
+}
+
+/// Normalizes the math of the `page` for the `format`, see
+/// [`convert_math_delimiters`] and [`split_display_math`]. For beamer a
+/// display math block over multiple lines is joined into one line instead,
+/// since the frames are converted line by line. Fenced code blocks are kept.
+pub fn normalize_math(page: &str, format: Format) -> String {
+    let mut text = String::new();
+    let mut fenced = false;
+    let mut display: Option<String> = None;
+    for line in page.split_inclusive('\n') {
+        let (content, newline) = match line.strip_suffix('\n') {
+            Some(content) => (content, "\n"),
+            None => (line, ""),
+        };
+        if content.trim_start().starts_with("```") {
+            fenced = !fenced;
+        }
+        if fenced || content.trim_start().starts_with("```") {
+            text.push_str(line);
+            continue;
+        }
+
+        let content = convert_math_delimiters(content);
+        if format != Format::Beamer {
+            text.push_str(&format!("{}{newline}", split_display_math(&content)));
+        } else if let Some(math) = &mut display {
+            math.push_str(&format!(" {}", content.trim()));
+            if content.contains("$$") {
+                text.push_str(&format!("{math}{newline}"));
+                display = None;
+            }
+        } else if content.matches("$$").count() % 2 == 1 {
+            display = Some(content.trim_end().to_string());
+        } else {
+            text.push_str(&format!("{content}{newline}"));
+        }
+    }
+    if let Some(math) = display {
+        text.push_str(&math);
+    }
+    text
+}
+
+/// Converts the properties of the markdown `slide` into Marp directives. The
+/// `class` property becomes the `_class` directive, which only applies to the
+/// slide itself.
+fn marp_slide(slide: &str) -> String {
+    let (properties, content) = split_properties(slide);
+    let directives = properties
+        .into_iter()
+        .map(|(name, value)| {
+            let name = if name == "class" { "_class" } else { name };
+            format!("<!-- {name}: {value} -->\n")
+        })
+        .collect::<String>();
+
+    format!("{directives}{content}")
+}
+
+/// Wraps the markdown `slide` into a reveal.js `<section>`. The `class`
+/// property becomes the class of the section, the `name` property its `id`
+/// and the `tags` property the `data-tags` attribute.
+fn reveal_section(slide: &str) -> String {
+    let (properties, content) = split_properties(slide);
+    let attributes = properties
+        .into_iter()
+        .map(|(name, value)| {
+            let name = match name {
+                "class" => "class",
+                "name" => "id",
+                _ => "data-tags",
+            };
+            format!(r#" {name}="{}""#, value.replace('"', "&quot;"))
+        })
+        .collect::<String>();
+
+    format!(
+        "<section data-markdown{attributes}>\n<textarea data-template>\n{}\n</textarea>\n</section>\n",
+        content.trim_end()
+    )
+}
+
+/// The frame options of beamer, which are taken from the `class` property.
+const BEAMER_FRAME_OPTIONS: &[&str] =
+    &["plain", "fragile", "allowframebreaks", "shrink", "squeeze"];
+
+/// Escapes the chars of the `text` that have a special meaning in LaTeX. The
+/// math between `$` or `$$` is kept.
+fn escape_latex(text: &str) -> String {
+    let mut escaped = String::new();
+    let mut is_math = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '$' => {
+                escaped.push(c);
+                if let Some(c) = chars.next_if_eq(&'$') {
+                    escaped.push(c);
+                }
+                is_math = !is_math;
+            }
+            '&' | '%' | '#' | '_' if !is_math => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Converts a markdown `line` with images into LaTeX. Every image becomes an
+/// `\includegraphics` with its path, the remaining text is escaped.
+fn latex_line(line: &str) -> String {
+    let mut text = String::new();
+    let mut rest = line;
+    while let Some(start) = rest.find("![") {
+        let Some((path, remaining)) = rest[start..]
+            .split_once("](")
+            .and_then(|(_, path)| path.split_once(')'))
+        else {
+            break;
+        };
+        text.push_str(&escape_latex(&rest[..start]));
+        text.push_str(&format!(
+            "\\includegraphics[width=\\linewidth,height=0.7\\textheight,keepaspectratio]{{{path}}}"
+        ));
+        rest = remaining;
+    }
+    text.push_str(&escape_latex(rest));
+    text
+}
+
+/// Converts the markdown `slide` into a beamer `frame`. The `class` property
+/// sets the frame options, where `top`, `middle` and `bottom` align the
+/// content and the classes in [`BEAMER_FRAME_OPTIONS`] are passed on. The
+/// `name` property becomes the `label` of the frame. The
+/// first heading becomes the `\frametitle`, lists become `itemize` and code
+/// blocks `verbatim`, which makes the frame `fragile`. Speaker notes after
+/// `???` are skipped.
+fn beamer_frame(slide: &str) -> String {
+    let (properties, content) = split_properties(slide);
+    let label = properties
+        .iter()
+        .find(|(name, _)| *name == "name")
+        .map(|(_, id)| format!("label={id}"));
+    let mut options = properties
+        .iter()
+        .filter(|(name, _)| *name == "class")
+        .flat_map(|(_, value)| value.split(',').map(str::trim))
+        .filter_map(|class| match class {
+            "top" => Some("t"),
+            "middle" => Some("c"),
+            "bottom" => Some("b"),
+            class => BEAMER_FRAME_OPTIONS
+                .iter()
+                .copied()
+                .find(|option| *option == class),
+        })
+        .collect::<Vec<_>>();
+
+    let mut body = String::new();
+    let mut title = None;
+    let mut is_list = false;
+    let mut is_code = false;
+    for line in content.lines() {
+        if line.trim_start().starts_with("```") {
+            body.push_str(if is_code {
+                "\\end{verbatim}\n"
+            } else {
+                "\\begin{verbatim}\n"
+            });
+            is_code = !is_code;
+            if !options.contains(&"fragile") {
+                options.push("fragile");
+            }
+            continue;
+        }
+        if is_code {
+            body.push_str(&format!("{line}\n"));
+            continue;
+        }
+        if line == "???" {
+            // The speaker notes are not part of the frame.
+            break;
+        }
+
+        let item = line.strip_prefix("- ").or_else(|| line.strip_prefix("* "));
+        if item.is_some() != is_list {
+            body.push_str(if is_list {
+                "\\end{itemize}\n"
+            } else {
+                "\\begin{itemize}\n"
+            });
+            is_list = !is_list;
+        }
+        if let Some(item) = item {
+            body.push_str(&format!("\\item {}\n", latex_line(item)));
+        } else if let Some(heading) = line
+            .trim_start_matches('#')
+            .strip_prefix(' ')
+            .filter(|_| line.starts_with('#'))
+        {
+            if title.is_none() {
+                title = Some(heading.trim().to_string());
+            } else {
+                body.push_str(&format!("\\textbf{{{}}}\n\n", latex_line(heading.trim())));
+            }
+        } else if !line.trim().is_empty() && line != "--" {
+            body.push_str(&format!("{}\n", latex_line(line)));
+        }
+    }
+    if is_list {
+        body.push_str("\\end{itemize}\n");
+    }
+    if is_code {
+        body.push_str("\\end{verbatim}\n");
+    }
+
+    let options = options
+        .into_iter()
+        .map(str::to_string)
+        .chain(label)
+        .collect::<Vec<_>>();
+    let options = if options.is_empty() {
+        String::new()
+    } else {
+        format!("[{}]", options.join(","))
+    };
+    let title = title
+        .map(|title| format!("\\frametitle{{{}}}\n", latex_line(&title)))
+        .unwrap_or_default();
+    format!("\\begin{{frame}}{options}\n{title}{body}\\end{{frame}}\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_separator, normalize_math, split_properties, Format, PAGE_SEPARATOR};
+
+    #[test]
+    fn test_normalize_math() {
+        let inline = "Energy \\(E = mc^2\\) and `\\(code\\)`\n";
+        assert_eq!(
+            normalize_math(inline, Format::Remark),
+            "Energy $E = mc^2$ and `\\(code\\)`\n"
+        );
+
+        let display = "The sum $$\\sum_i x_i$$ is\n$$x$$\n\\[\nx_1\n\\]\n";
+        assert_eq!(
+            normalize_math(display, Format::Remark),
+            "The sum\n$$\\sum_i x_i$$\nis\n$$x$$\n$$\nx_1\n$$\n"
+        );
+        assert_eq!(
+            normalize_math(display, Format::Beamer),
+            "The sum $$\\sum_i x_i$$ is\n$$x$$\n$$ x_1 $$\n"
+        );
+
+        let code = "```python\nx = \"\\(a\\) $$b$$ c\"\n```\n";
+        assert_eq!(normalize_math(code, Format::Remark), code);
+    }
+
+    #[test]
+    fn test_separator() {
+        assert_eq!(check_separator(" *** ").unwrap(), "***");
+        assert!(check_separator(" ").is_err());
+        assert!(check_separator("***\n---").is_err());
+        assert_eq!(check_separator("<!-- slide -->").unwrap(), "<!-- slide -->");
+        assert_eq!(check_separator("___").unwrap(), "___");
+        for separator in [
+            "--",
+            "???",
+            "class: center",
+            "# Slide",
+            "===",
+            "> quote",
+            "```",
+            "~~~",
+            "| a |",
+            "- item",
+            "* item",
+            "+",
+            "1. item",
+            "2)",
+        ] {
+            assert!(check_separator(separator).is_err(), "{separator}");
+        }
+
+        let pages = vec![
+            "# First\n***\n\n# Second".to_string(),
+            "---\ntitle: Test\n---\n".to_string(),
+        ];
+        assert_eq!(
+            Format::Remark.render(&pages, "Title", "***"),
+            "# First\n***\n\n# Second\n***\n\n---\ntitle: Test\n---\n"
+        );
+        assert_eq!(
+            Format::Remark.render(&pages, "Title", PAGE_SEPARATOR),
+            "# First\n***\n\n# Second\n---\n\n---\ntitle: Test\n---\n"
+        );
+    }
+
+    #[test]
+    fn test_add_front_matter() {
+        let pages = vec!["# First".to_string()];
+        let front_matter = "---\ntheme: gaia\npaginate: true\n---\n";
+
+        let text = Format::Remark.render(&pages, "Title", PAGE_SEPARATOR);
+        assert_eq!(
+            Format::Remark.add_front_matter(&text, front_matter),
+            "---\ntheme: gaia\npaginate: true\n---\n\n# First"
+        );
+
+        let text = Format::Marp.render(&pages, "Title", PAGE_SEPARATOR);
+        assert_eq!(
+            Format::Marp.add_front_matter(&text, "---\ntheme: gaia\ntitle: Other\n---"),
+            "---\nmarp: true\ntheme: gaia\ntitle: Other\n---\n\n# First\n"
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("Marp".parse::<Format>().unwrap(), Format::Marp);
+        let err = "pdf".parse::<Format>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown format 'pdf'. Use one of remark, marp, reveal, beamer."
+        );
+    }
+
+    #[test]
+    fn test_split_properties() {
+        let (properties, content) = split_properties("\nclass: center\n\ntags: fire\n\nNote: text");
+        assert_eq!(properties, vec![("class", "center"), ("tags", "fire")]);
+        assert_eq!(content, "Note: text");
+    }
+
+    #[test]
+    fn test_render_marp() {
+        let pages = vec![
+            "class: lead, invert\n\nA paragraph\n---\n\nText".to_string(),
+            "tags: fire\n\n# Last\n".to_string(),
+        ];
+
+        let text = Format::Marp.render(&pages, "Title", PAGE_SEPARATOR);
+        assert!(text.starts_with("---\nmarp: true\ntitle: \"Title\"\n---\n\n"));
+        assert!(
+            text.contains("<!-- _class: lead, invert -->\nA paragraph\n\n---\n\nText\n\n---\n\n")
+        );
+        assert!(text.contains("<!-- tags: fire -->\n# Last\n"));
+        assert!(!text.contains("\n\n\n"));
+    }
+
+    #[test]
+    fn test_render_beamer() {
+        let pages = vec![
+            "class: middle\n\n# First & {{title}}\n- Item\n---\n\n## Second\n![Plot](../images/plot.png)\n".to_string(),
+            "name: code\n\n```python\nx = 1\n```\n".to_string(),
+            "## Math\n$a_b$ and a_b\n$$\nx_1\n$$\n".to_string(),
+        ];
+
+        let text = Format::Beamer.render(&pages, "Title", PAGE_SEPARATOR);
+        assert!(text.starts_with("\\documentclass{beamer}\n"));
+        assert!(text.ends_with("\\end{document}\n"));
+        assert_eq!(text.matches("\\begin{frame}").count(), 4);
+        assert_eq!(text.matches("\\end{frame}").count(), 4);
+        assert!(text.contains(
+            "\\begin{frame}[c]\n\\frametitle{First \\& Title}\n\\begin{itemize}\n\\item Item\n\\end{itemize}\n\\end{frame}\n"
+        ));
+        assert!(text.contains("\\includegraphics[width=\\linewidth,height=0.7\\textheight,keepaspectratio]{../images/plot.png}"));
+        assert!(text.contains(
+            "\\begin{frame}[fragile,label=code]\n\\begin{verbatim}\nx = 1\n\\end{verbatim}\n"
+        ));
+        assert!(text.contains("\\frametitle{Math}\n$a_b$ and a\\_b\n$$ x_1 $$\n\\end{frame}"));
+    }
+
+    #[test]
+    fn test_render() {
+        let pages = vec![
+            "# First {{title}}".to_string(),
+            String::new(),
+            "class: center\n\n# Second\n---\n\nname: third\n\n# Third\n".to_string(),
+        ];
+
+        assert_eq!(
+            Format::Remark.render(&pages, "Title", PAGE_SEPARATOR),
+            "# First Title\n---\n\nclass: center\n\n# Second\n---\n\nname: third\n\n# Third\n"
+        );
+        assert_eq!(
+            Format::Marp.render(&pages, "Title", PAGE_SEPARATOR),
+            "---\nmarp: true\ntitle: \"Title\"\n---\n\n# First Title\n\n---\n\n<!-- _class: center -->\n# Second\n\n---\n\n<!-- name: third -->\n# Third\n"
+        );
+        assert_eq!(
+            Format::Reveal.render(&pages, "Title", PAGE_SEPARATOR),
+            "<section data-markdown>\n<textarea data-template>\n# First Title\n</textarea>\n</section>\n\
+             <section data-markdown class=\"center\">\n<textarea data-template>\n# Second\n</textarea>\n</section>\n\
+             <section data-markdown id=\"third\">\n<textarea data-template>\n# Third\n</textarea>\n</section>\n"
+        );
+    }
+}
@@ -1,14 +1,85 @@
 //! Retrieve all possible paths as a [`Vec<PathBuf>`] from the given arguments. If a directory path is passed,
-//! this function will recursively search for all `.ipynb` notebooks within the directory.
-use log::info;
-use std::{ffi::OsStr, fs, path::PathBuf};
+//! this function will recursively search for all `.ipynb` notebooks within the directory, or the files with one of the
+//! extensions of [`SearchOptions::extensions`].
+use glob::Pattern;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use log::{debug, info, warn};
+use std::{
+    collections::HashSet,
+    ffi::OsStr,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The patterns of the paths that are always excluded when searching a
+/// directory.
+const DEFAULT_EXCLUDES: &[&str] = &[".ipynb_checkpoints"];
+
+/// The extensions of the files that are collected when searching a
+/// directory, if [`SearchOptions::extensions`] is empty.
+const DEFAULT_EXTENSIONS: &[&str] = &["ipynb"];
+
+/// The options for searching the directories for notebooks.
+#[derive(Debug, Default, Clone)]
+pub struct SearchOptions {
+    /// The glob patterns of the files and directories that are skipped.
+    pub exclude: Vec<String>,
+    /// The depth of the subdirectories that are searched. `0` only searches
+    /// the given directory and `None` has no limit.
+    pub max_depth: Option<usize>,
+    /// A file listing the paths of the presentation in order, see
+    /// [`read_manifest`].
+    pub manifest: Option<PathBuf>,
+    /// The extensions of the files that are collected when searching a
+    /// directory, e.g. `md`. If empty, only [`DEFAULT_EXTENSIONS`] are
+    /// collected.
+    pub extensions: Vec<String>,
+    /// Whether the paths ignored by the `.gitignore` files are skipped, see
+    /// [`parent_gitignores`].
+    pub respect_gitignore: bool,
+}
+
+/// Reads the paths of the `manifest` file, one per line. Empty lines and
+/// lines starting with `#` are skipped and relative paths are resolved
+/// against the directory of the manifest.
+///
+/// # Errors
+///
+/// This function will return an error if the manifest could not be read or
+/// an entry is a directory.
+pub fn read_manifest(manifest: &Path) -> Result<Vec<PathBuf>, io::Error> {
+    let directory = manifest.parent().unwrap_or(Path::new(""));
+    fs::read_to_string(manifest)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let path = directory.join(line);
+            if path.is_dir() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("The manifest entry {path:?} is a directory."),
+                ));
+            }
+            Ok(path)
+        })
+        .collect()
+}
 
 /// Converts a slice of [`String`] paths into a [`Vec<PathBuf>`] and includes
 /// all `.ipynb` files in any directories encountered during the process.
 ///
 /// If any of the paths passed in represent directories, this function will
-/// search the directory recursively and add any `.ipynb` files found to the
-/// final output.
+/// search the directory recursively and add any `.ipynb` files, or files with
+/// one of the [`SearchOptions::extensions`], found to the final output. Files and directories matching one of the glob patterns in
+/// [`SearchOptions::exclude`] or [`DEFAULT_EXCLUDES`] are skipped during the
+/// search and subdirectories deeper than [`SearchOptions::max_depth`] are not
+/// searched. With [`SearchOptions::respect_gitignore`] the paths ignored by
+/// the `.gitignore` files are skipped as well.
+///
+/// With a [`SearchOptions::manifest`] the paths of the manifest are used in
+/// their order and the directories of the passed in `paths` are not searched,
+/// only the files are kept in front of the manifest paths.
 ///
 /// # Errors
 ///
@@ -16,10 +87,60 @@ use std::{ffi::OsStr, fs, path::PathBuf};
 /// - The provided path doesn't exist.
 /// - The process lacks permissions to view the contents.
 /// - The path points at a non-directory file.
-pub fn get_paths_from_strings(paths: &[String]) -> Result<Vec<PathBuf>, std::io::Error> {
+/// - An exclude pattern is invalid.
+/// - The manifest could not be read.
+pub fn get_paths_from_strings(
+    paths: &[String],
+    options: &SearchOptions,
+) -> Result<Vec<PathBuf>, std::io::Error> {
+    let exclude = DEFAULT_EXCLUDES
+        .iter()
+        .copied()
+        .chain(options.exclude.iter().map(String::as_str))
+        .map(|pattern| {
+            Pattern::new(pattern).map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid exclude pattern '{pattern}'. {err}"),
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let Some(manifest) = &options.manifest {
+        let mut files = vec![];
+        for path in paths.iter().map(PathBuf::from) {
+            if path.is_dir() {
+                warn!("The directory {path:?} is skipped, because a manifest is given.");
+            } else {
+                files.push(path);
+            }
+        }
+        files.extend(read_manifest(manifest)?);
+        info!("The following paths are evaluated: {files:?}");
+        return Ok(files);
+    }
+
+    let extensions = if options.extensions.is_empty() {
+        DEFAULT_EXTENSIONS.to_vec()
+    } else {
+        options
+            .extensions
+            .iter()
+            .map(|extension| extension.trim_start_matches('.'))
+            .collect()
+    };
     let paths = paths
         .iter()
-        .map(|path| get_path_from_string(path))
+        .map(|path| {
+            get_path_from_string(
+                path,
+                &exclude,
+                &extensions,
+                options.max_depth,
+                options.respect_gitignore,
+            )
+        })
         .collect::<Result<Vec<Vec<PathBuf>>, std::io::Error>>()?
         .into_iter()
         .flatten()
@@ -30,21 +151,173 @@ pub fn get_paths_from_strings(paths: &[String]) -> Result<Vec<PathBuf>, std::io:
     Ok(paths)
 }
 
-/// Helper function for `get_paths_from_strings`
-fn get_path_from_string(path: &str) -> Result<Vec<PathBuf>, std::io::Error> {
-    let mut paths = vec![PathBuf::from(path)];
+/// Expands the glob `pattern` into the matching paths in alphabetical order.
+///
+/// # Errors
+///
+/// This function will return an error if the pattern is invalid, a matching
+/// path could not be read or the pattern matches no path.
+fn expand_glob(pattern: &str) -> Result<Vec<PathBuf>, io::Error> {
+    let paths = glob::glob(pattern)
+        .map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid pattern '{pattern}'. {err}"),
+            )
+        })?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(io::Error::from)?;
+
+    if paths.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("The pattern '{pattern}' matches no path."),
+        ));
+    }
+    Ok(paths)
+}
+
+/// Returns `true` if the file name or the whole `path` matches one of the
+/// `exclude` patterns.
+fn is_excluded(path: &Path, exclude: &[Pattern]) -> bool {
+    exclude.iter().any(|pattern| {
+        pattern.matches_path(path)
+            || path
+                .file_name()
+                .and_then(OsStr::to_str)
+                .is_some_and(|name| pattern.matches(name))
+    })
+}
+
+/// The file with the patterns of the paths that are ignored by git.
+const GITIGNORE: &str = ".gitignore";
+
+/// Reads the [`GITIGNORE`] file of the directory `dir`, if it exists. Invalid
+/// patterns are logged and skipped.
+fn read_gitignore(dir: &Path) -> Option<Gitignore> {
+    let path = dir.join(GITIGNORE);
+    if !path.is_file() {
+        return None;
+    }
+    let mut builder = GitignoreBuilder::new(std::path::absolute(dir).ok()?);
+    if let Some(err) = builder.add(&path) {
+        warn!("The file {path:?} could not be read completely. {err}");
+    }
+    match builder.build() {
+        Ok(gitignore) => Some(gitignore),
+        Err(err) => {
+            warn!("The file {path:?} is skipped. {err}");
+            None
+        }
+    }
+}
+
+/// Returns the [`GITIGNORE`] files of the parent directories of the
+/// directory `dir` up to the root of its git repository, the outermost
+/// first. Returns no file, if the directory is not part of a repository.
+fn parent_gitignores(dir: &Path) -> Vec<Gitignore> {
+    let Ok(dir) = std::path::absolute(dir) else {
+        return vec![];
+    };
+    let mut gitignores = vec![];
+    for (i, ancestor) in dir.ancestors().enumerate() {
+        if i > 0 {
+            gitignores.extend(read_gitignore(ancestor));
+        }
+        if ancestor.join(".git").exists() {
+            gitignores.reverse();
+            return gitignores;
+        }
+    }
+    vec![]
+}
+
+/// Returns `true` if the `path` is ignored by the `gitignores`, where a
+/// later, nested file overrides the earlier ones, e.g. with `!book.ipynb`.
+fn is_ignored(path: &Path, is_dir: bool, gitignores: &[Gitignore]) -> bool {
+    let Ok(path) = std::path::absolute(path) else {
+        return false;
+    };
+    gitignores
+        .iter()
+        .rev()
+        .map(|gitignore| gitignore.matched(&path, is_dir))
+        .find(|matched| !matched.is_none())
+        .is_some_and(|matched| matched.is_ignore())
+}
+
+/// Helper function for `get_paths_from_strings`. A path with the glob
+/// metacharacters `*`, `?` or `[` is expanded first. Directories that were
+/// already visited, e.g. through a symbolic link to a parent directory, and
+/// paths inside a directory matching one of the `exclude` patterns are
+/// skipped. Only the files with one of the `extensions` are collected from a
+/// directory. Subdirectories deeper than `max_depth` are not searched. With
+/// `respect_gitignore` the paths ignored by the `.gitignore` files of the
+/// searched directories and their parent directories in the repository are
+/// skipped.
+fn get_path_from_string(
+    path: &str,
+    exclude: &[Pattern],
+    extensions: &[&str],
+    max_depth: Option<usize>,
+    respect_gitignore: bool,
+) -> Result<Vec<PathBuf>, std::io::Error> {
+    let paths = if path.contains(['*', '?', '[']) {
+        expand_glob(path)?
+    } else {
+        vec![PathBuf::from(path)]
+    };
+    let mut paths = paths
+        .into_iter()
+        .map(|path| {
+            let gitignores = if respect_gitignore && path.is_dir() {
+                parent_gitignores(&path)
+            } else {
+                vec![]
+            };
+            (path, 0, gitignores)
+        })
+        .collect::<Vec<_>>();
+    let mut visited = HashSet::new();
     let mut i = 0;
 
     while i < paths.len() {
-        let path = &paths[i];
+        let (path, depth, gitignores) = &paths[i];
+        let depth = *depth;
 
         if path.is_dir() {
+            if !visited.insert(fs::canonicalize(path)?) {
+                warn!("The directory {path:?} was already visited and is skipped.");
+                paths.remove(i);
+                continue;
+            }
+
+            let mut gitignores = gitignores.clone();
+            if respect_gitignore {
+                gitignores.extend(read_gitignore(path));
+            }
             let dirs = fs::read_dir(path)?;
             for dir in dirs {
                 let dir = dir?;
                 let path = dir.path();
-                if path.is_dir() || path.extension() == Some(OsStr::new("ipynb")) {
-                    paths.push(path);
+                if is_excluded(&path, exclude) {
+                    debug!("The path {path:?} is excluded.");
+                    continue;
+                }
+                if is_ignored(&path, path.is_dir(), &gitignores) {
+                    debug!("The path {path:?} is ignored by git.");
+                    continue;
+                }
+                if path.is_dir() {
+                    if max_depth.is_none_or(|max_depth| depth < max_depth) {
+                        paths.push((path, depth + 1, gitignores.clone()));
+                    }
+                } else if path
+                    .extension()
+                    .and_then(OsStr::to_str)
+                    .is_some_and(|extension| extensions.contains(&extension))
+                {
+                    paths.push((path, depth, vec![]));
                 }
             }
             paths.remove(i);
@@ -53,5 +326,258 @@ fn get_path_from_string(path: &str) -> Result<Vec<PathBuf>, std::io::Error> {
         }
     }
 
-    Ok(paths)
+    Ok(paths.into_iter().map(|(path, _, _)| path).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::PathBuf;
+
+    use crate::{create_presentation::collect_pages, notebook::NotebookOptions};
+
+    use super::{get_path_from_string, get_paths_from_strings, SearchOptions, DEFAULT_EXTENSIONS};
+
+    #[test]
+    fn test_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let notebooks = dir.path().join("notebooks");
+        std::fs::create_dir(&notebooks).unwrap();
+        for name in ["a", "b"] {
+            std::fs::write(
+                notebooks.join(format!("{name}.ipynb")),
+                format!(
+                    r#"{{"cells": [{{"cell_type": "markdown", "metadata": {{}}, "source": ["<!--! new; inject[# {name}] -->"]}}]}}"#
+                ),
+            )
+            .unwrap();
+        }
+        std::fs::write(dir.path().join("intro.md"), "# Intro").unwrap();
+        let manifest = dir.path().join("manifest.txt");
+        std::fs::write(
+            &manifest,
+            "# The order of the course\nnotebooks/b.ipynb\n\nintro.md\n  notebooks/a.ipynb\n",
+        )
+        .unwrap();
+
+        let paths = get_paths_from_strings(
+            &[notebooks.to_str().unwrap().to_string()],
+            &SearchOptions {
+                manifest: Some(manifest.clone()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                dir.path().join("notebooks/b.ipynb"),
+                dir.path().join("intro.md"),
+                dir.path().join("notebooks/a.ipynb"),
+            ]
+        );
+
+        let pages = collect_pages(
+            dir.path().join("output.rmd"),
+            &NotebookOptions::default(),
+            &paths,
+            None,
+        )
+        .unwrap()
+        .0;
+        assert_eq!(pages, vec!["# b", "# Intro", "# a"]);
+
+        std::fs::write(&manifest, "notebooks").unwrap();
+        let options = SearchOptions {
+            manifest: Some(manifest),
+            ..Default::default()
+        };
+        assert!(get_paths_from_strings(&[], &options).is_err());
+        let options = SearchOptions {
+            manifest: Some(PathBuf::from("missing.txt")),
+            ..Default::default()
+        };
+        assert!(get_paths_from_strings(&[], &options).is_err());
+    }
+
+    #[test]
+    fn test_exclude() {
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoints = dir.path().join(".ipynb_checkpoints");
+        std::fs::create_dir(&checkpoints).unwrap();
+        std::fs::write(checkpoints.join("book-checkpoint.ipynb"), "").unwrap();
+        std::fs::write(dir.path().join("book.ipynb"), "").unwrap();
+        std::fs::write(dir.path().join("draft_book.ipynb"), "").unwrap();
+
+        let paths = get_paths_from_strings(
+            &[dir.path().to_str().unwrap().to_string()],
+            &SearchOptions {
+                exclude: vec!["draft_*".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(paths, vec![dir.path().join("book.ipynb")]);
+    }
+
+    #[test]
+    fn test_extensions() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["book.ipynb", "snippet.md", "notes.txt"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let input = [dir.path().to_str().unwrap().to_string()];
+
+        let paths = get_paths_from_strings(&input, &SearchOptions::default()).unwrap();
+        assert_eq!(paths, vec![dir.path().join("book.ipynb")]);
+
+        let mut paths = get_paths_from_strings(
+            &input,
+            &SearchOptions {
+                extensions: vec!["md".to_string(), ".ipynb".to_string()],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![dir.path().join("book.ipynb"), dir.path().join("snippet.md")]
+        );
+    }
+
+    #[test]
+    fn test_respect_gitignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let notebooks = dir.path().join("notebooks");
+        let generated = notebooks.join("generated");
+        std::fs::create_dir_all(&generated).unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "generated/\n*_out.ipynb\n").unwrap();
+        std::fs::write(
+            notebooks.join(".gitignore"),
+            "draft.ipynb\n!keep_out.ipynb\n",
+        )
+        .unwrap();
+        for name in [
+            "book.ipynb",
+            "draft.ipynb",
+            "book_out.ipynb",
+            "keep_out.ipynb",
+        ] {
+            std::fs::write(notebooks.join(name), "").unwrap();
+        }
+        std::fs::write(generated.join("book.ipynb"), "").unwrap();
+        let input = [notebooks.to_str().unwrap().to_string()];
+
+        let mut paths = get_paths_from_strings(
+            &input,
+            &SearchOptions {
+                respect_gitignore: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                notebooks.join("book.ipynb"),
+                notebooks.join("keep_out.ipynb")
+            ]
+        );
+
+        let paths = get_paths_from_strings(&input, &SearchOptions::default()).unwrap();
+        assert_eq!(paths.len(), 5);
+    }
+
+    #[test]
+    fn test_max_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first");
+        let second = first.join("second");
+        std::fs::create_dir_all(&second).unwrap();
+        for path in [dir.path(), &first, &second] {
+            std::fs::write(path.join("book.ipynb"), "").unwrap();
+        }
+
+        let mut paths = get_path_from_string(
+            dir.path().to_str().unwrap(),
+            &[],
+            DEFAULT_EXTENSIONS,
+            Some(1),
+            false,
+        )
+        .unwrap();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![dir.path().join("book.ipynb"), first.join("book.ipynb")]
+        );
+
+        let paths = get_path_from_string(
+            dir.path().to_str().unwrap(),
+            &[],
+            DEFAULT_EXTENSIONS,
+            Some(0),
+            false,
+        )
+        .unwrap();
+        assert_eq!(paths, vec![dir.path().join("book.ipynb")]);
+    }
+
+    #[test]
+    fn test_glob() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["week2.ipynb", "week1.ipynb", "notes.md"] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+
+        let pattern = dir.path().join("*.ipynb");
+        let paths = get_path_from_string(
+            pattern.to_str().unwrap(),
+            &[],
+            DEFAULT_EXTENSIONS,
+            None,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            paths,
+            vec![
+                dir.path().join("week1.ipynb"),
+                dir.path().join("week2.ipynb")
+            ]
+        );
+
+        let pattern = dir.path().join("*.rmd");
+        let err = get_path_from_string(
+            pattern.to_str().unwrap(),
+            &[],
+            DEFAULT_EXTENSIONS,
+            None,
+            false,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("matches no path"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loop() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub_dir = dir.path().join("sub");
+        std::fs::create_dir(&sub_dir).unwrap();
+        std::fs::write(sub_dir.join("book.ipynb"), "").unwrap();
+        std::os::unix::fs::symlink(dir.path(), sub_dir.join("loop")).unwrap();
+
+        let paths = get_path_from_string(
+            dir.path().to_str().unwrap(),
+            &[],
+            DEFAULT_EXTENSIONS,
+            None,
+            false,
+        )
+        .unwrap();
+        assert_eq!(paths, vec![sub_dir.join("book.ipynb")]);
+    }
 }
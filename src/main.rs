@@ -8,17 +8,32 @@
 #![warn(clippy::doc_markdown)]
 
 mod arguments;
+mod broken_images;
 mod commands;
 mod create_presentation;
+mod deck_manifest;
+mod format;
 mod get_files;
 mod notebook;
 mod path;
+mod report;
+mod search_index;
+mod stats;
+mod watch;
 
 use anyhow::Result;
-use arguments::get_arguments;
-use log::{error, info, LevelFilter};
+use arguments::{get_arguments, Arguments};
+use create_presentation::PageCache;
+use format::Format;
+use get_files::SearchOptions;
+use log::{debug, error, info, warn, LevelFilter};
+use notebook::NotebookOptions;
 use simple_logger::SimpleLogger;
-use std::{path::PathBuf, str::FromStr};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
 
 fn main() {
     let run = run();
@@ -27,11 +42,145 @@ fn main() {
             info!("Program has run.")
         }
         Err(err) => {
-            error!("{}", err)
+            error!("{}", report::render(&err, report::use_color()))
         }
     }
 }
 
+/// Converts the notebooks and files at the `paths` into one presentation and
+/// writes it to the `output_path`. The pages of unchanged notebooks are
+/// reused from the `cache`, if given. A remark presentation is streamed to
+/// the output, see [`create_presentation::stream_presentation`], unless
+/// [`Arguments::needs_whole_deck`].
+///
+/// # Errors
+///
+/// This function will return an error if a notebook could not be converted
+/// or a file could not be written.
+fn write_deck(
+    args: &Arguments,
+    format: Format,
+    options: &NotebookOptions,
+    output_path: PathBuf,
+    mut paths: Vec<PathBuf>,
+    cache: Option<&PageCache>,
+) -> Result<()> {
+    info!("Write the presentation {output_path:?}");
+    let front_matter = match &args.front_matter {
+        Some(path) => Some(fs::read_to_string(path).map_err(|err| {
+            anyhow::Error::msg(format!("Unable to read the front matter {path:?}. {err}"))
+        })?),
+        None => None,
+    };
+    let separator = options.separator();
+    let title_page = create_presentation::title_page(
+        args.title.as_deref(),
+        args.author.as_deref(),
+        args.date.as_deref(),
+        args.title_class(),
+    );
+    if format == Format::Remark && !args.needs_whole_deck() {
+        let title =
+            create_presentation::document_title(args.title.as_deref(), false, &[], &output_path);
+        return create_presentation::stream_presentation(
+            &output_path,
+            options,
+            &paths,
+            cache,
+            &title,
+            front_matter.as_deref(),
+        );
+    }
+
+    let (mut pages, mut notebook_pages) =
+        create_presentation::collect_pages(output_path.clone(), options, &paths, cache)?;
+    let title = create_presentation::document_title(
+        args.title.as_deref(),
+        args.title_from_first_heading,
+        &pages,
+        &output_path,
+    );
+    let table_of_contents = args
+        .toc
+        .then(|| create_presentation::table_of_contents(&pages))
+        .flatten();
+    let start = create_presentation::title_page_index(&paths);
+    let mut index = start;
+    if let Some(title_page) = title_page {
+        pages.insert(index, title_page);
+        paths.insert(index, output_path.clone());
+        notebook_pages.insert(index, vec![]);
+        index += 1;
+    }
+    if let Some(table_of_contents) = table_of_contents {
+        pages.insert(index, table_of_contents);
+        paths.insert(index, output_path.clone());
+        notebook_pages.insert(index, vec![]);
+        index += 1;
+    }
+    if args.stats {
+        // The statistics are written to stderr, so they do not mix with a
+        // presentation written to stdout.
+        eprintln!(
+            "{}\n{}",
+            output_path.display(),
+            stats::report(
+                &pages,
+                args.dense_words.unwrap_or(stats::DEFAULT_DENSE_WORDS),
+                separator,
+            )
+        );
+    }
+    if let Some(search_index) = &args.search_index {
+        search_index::write_search_index(PathBuf::from_str(search_index)?, &pages, separator)?;
+    }
+    if let Some(manifest_out) = &args.manifest_out {
+        deck_manifest::write_deck_manifest(
+            PathBuf::from_str(manifest_out)?,
+            &output_path,
+            options,
+            &paths,
+            &pages,
+            &notebook_pages,
+        )?;
+    }
+    if let Some(broken_images) = &args.broken_images {
+        broken_images::write_broken_images(
+            PathBuf::from_str(broken_images)?,
+            &output_path,
+            &paths,
+            &pages,
+            separator,
+        )?;
+    }
+    if args.header.is_some() || args.footer.is_some() {
+        create_presentation::add_header_and_footer(
+            &output_path,
+            options.assets.as_deref(),
+            &mut pages[start..],
+            args.header.as_deref(),
+            args.footer.as_deref(),
+            separator,
+        )?;
+    }
+    if args.page_numbers {
+        let first = if args.number_title_pages {
+            start
+        } else {
+            index
+        };
+        create_presentation::add_page_numbers(&mut pages[first..], format, separator);
+    }
+    create_presentation::write_presentation(
+        output_path,
+        pages,
+        format,
+        &title,
+        front_matter.as_deref(),
+        separator,
+    )
+}
+
 /// Run the program and return an error if any occurs.
 ///
 /// # Errors
@@ -39,13 +188,17 @@ fn main() {
 /// This function will return an error if any intern system fails. Normally
 /// only, when a tag was wrongly defined.
 fn run() -> Result<()> {
-    let args = match get_arguments() {
+    let mut args = match get_arguments() {
         Ok(ok) => ok,
         Err(err) => {
             println!("{err}");
             return Err(err);
         }
     };
+    if args.version {
+        println!("{} {}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+        return Ok(());
+    }
 
     SimpleLogger::new()
         .with_level(LevelFilter::Off)
@@ -61,18 +214,135 @@ fn run() -> Result<()> {
         )
         .init()?;
 
-    let output_path = PathBuf::from_str(&args.output)?;
-    if !args.force && output_path.is_file() && output_path.exists() {
-        return Err(anyhow::Error::msg(format!(
-            r#"File already exist {:?}. Use "-f" to force an override."#,
-            output_path
-        )));
+    let format = args.format().parse::<Format>()?;
+    if args.output.is_empty() {
+        args.output =
+            arguments::default_output(&args.input, format.extension()).unwrap_or_default();
     }
+    let options = NotebookOptions {
+        relative_to: args.relative_to.as_deref().map(PathBuf::from),
+        assets: args.assets.as_deref().map(PathBuf::from),
+        base_url: args.base_url.clone(),
+        embed_images: args.embed_images,
+        only_tags: arguments::split_list(args.only_tags.as_deref()),
+        profile: args.profile.clone(),
+        presenter: args.presenter,
+        trim_empty: args.trim_empty,
+        skip_tags: arguments::split_list(args.skip_tags.as_deref()),
+        line_numbers: args.line_numbers,
+        strict: args.strict,
+        auto_id: args.auto_id,
+        keep_commands: args.keep_commands,
+        separator: match &args.separator {
+            Some(_) if format != Format::Remark => {
+                warn!("The separator is not supported by the format {format}.");
+                None
+            }
+            Some(separator) => Some(format::check_separator(separator)?),
+            None => None,
+        },
+    };
 
-    let paths = get_files::get_paths_from_strings(&args.input)?;
-    let pages = create_presentation::collect_pages(PathBuf::from_str(&args.output)?, &paths)?;
-    let output_path = PathBuf::from_str(&args.output)?;
-    create_presentation::write_presentation(output_path, pages)?;
+    let cache = PageCache::default();
+    let cache = args.watch.then_some(&cache);
+    let outputs = build(&args, format, &options, args.force, cache)?;
+    if args.watch && !args.list {
+        let mut ignored = outputs;
+        ignored.extend(args.search_index.iter().map(PathBuf::from));
+        ignored.extend(args.manifest_out.iter().map(PathBuf::from));
+        ignored.extend(args.broken_images.iter().map(PathBuf::from));
+        watch::watch(&args.input, &ignored, || {
+            let conversions = cache.map(PageCache::conversions).unwrap_or_default();
+            build(&args, format, &options, true, cache)?;
+            debug!(
+                "Converted {} changed notebooks.",
+                cache.map(PageCache::conversions).unwrap_or_default() - conversions
+            );
+            Ok(())
+        })?;
+    }
 
     Ok(())
 }
+
+/// Resolves the input paths and writes the presentation, or one presentation
+/// per notebook with `--separate`. Existing files are only overridden if
+/// `force` is set. The pages of unchanged notebooks are reused from the
+/// `cache`, if given. Returns the paths of the written presentations.
+///
+/// # Errors
+///
+/// This function will return an error if the input paths could not be
+/// resolved, an output file may not be overridden or a presentation could
+/// not be written.
+fn build(
+    args: &Arguments,
+    format: Format,
+    options: &NotebookOptions,
+    force: bool,
+    cache: Option<&PageCache>,
+) -> Result<Vec<PathBuf>> {
+    let output_path = PathBuf::from_str(&args.output)?;
+
+    let mut paths = get_files::get_paths_from_strings(
+        &args.input,
+        &SearchOptions {
+            exclude: args.exclude.clone(),
+            max_depth: args.max_depth,
+            manifest: args.manifest.as_deref().map(PathBuf::from),
+            extensions: arguments::split_list(args.include_extensions.as_deref()),
+            respect_gitignore: args.respect_gitignore,
+        },
+    )?;
+    if args.reverse_notebooks {
+        paths.reverse();
+    }
+
+    if args.list {
+        for path in &paths {
+            println!("{}", path.display());
+        }
+        return Ok(vec![]);
+    }
+
+    if args.dry_run {
+        let errors = create_presentation::validate_notebooks(&paths, options);
+        if errors > 0 {
+            return Err(anyhow::Error::msg(format!(
+                "Found {errors} errors in the notebooks."
+            )));
+        }
+        info!("The notebooks have no errors.");
+        return Ok(vec![]);
+    }
+
+    let decks = if args.separate || create_presentation::is_output_dir(&args.output) {
+        if output_path == Path::new(create_presentation::STDIO) {
+            return Err(anyhow::Error::msg(
+                r#"The output can not be written to stdout with "--separate"."#,
+            ));
+        }
+        if args.search_index.is_some()
+            || args.manifest_out.is_some()
+            || args.broken_images.is_some()
+        {
+            return Err(anyhow::Error::msg(
+                r#""--search-index", "--manifest-out" and "--broken-images" can not be used with "--separate"."#,
+            ));
+        }
+        fs::create_dir_all(&output_path)?;
+        create_presentation::separate_decks(&output_path, &paths, format.extension())
+    } else {
+        vec![(output_path, paths)]
+    };
+    for (output_path, _) in &decks {
+        create_presentation::check_output_path(output_path, force)?;
+    }
+    let mut outputs = vec![];
+    for (output_path, paths) in decks {
+        outputs.push(output_path.clone());
+        write_deck(args, format, options, output_path, paths, cache)?;
+    }
+
+    Ok(outputs)
+}
@@ -1,17 +1,150 @@
 //! Load and read a `.ipynb` notebook with `serde` and apply the assigned tags.
 use anyhow::Result;
-use log::{error, debug};
-use serde::{Deserialize, Serialize};
+use log::{debug, error, warn};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::{
-    fs,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt, fs,
     path::{Path, PathBuf},
 };
 
 use crate::{
-    commands::{self, Command},
-    path::{replace_paths, wrap_image},
+    commands::{self, Command, ParseError},
+    format::PAGE_SEPARATOR,
+    path::{embed_images, prefix_paths, replace_paths, wrap_image},
+    report::{self, CommandError},
 };
 
+/// The name of the variable that holds the title of the presentation, if it
+/// is not defined with [`Command::SetVariable`].
+pub const TITLE_VARIABLE: &str = "title";
+
+/// The language of the fenced code blocks of [`Command::Code`], if the
+/// notebook does not define the language of its kernel.
+const DEFAULT_LANGUAGE: &str = "python";
+
+/// The mime types of the image outputs that are embedded with
+/// [`Command::AddImageToPage`], from the richest to the poorest.
+const IMAGE_MIME_TYPES: &[&str] = &["image/png", "image/jpeg", "image/gif"];
+
+/// The options that change how a [`Notebook`] is converted to pages.
+#[derive(Debug, Default, Clone)]
+pub struct NotebookOptions {
+    /// The directory the relative paths are resolved against instead of the
+    /// directory of the notebook.
+    pub relative_to: Option<PathBuf>,
+    /// The directory relative to the output, where the local images are
+    /// copied to, instead of pointing to the originals.
+    pub assets: Option<PathBuf>,
+    /// The url the relative paths are joined onto instead of pointing to
+    /// the files relative to the output.
+    pub base_url: Option<String>,
+    /// Whether the local images are embedded as `data:` urls.
+    pub embed_images: bool,
+    /// If not empty, only cells with one of these tags or without any tag
+    /// that is not a command are included.
+    pub only_tags: Vec<String>,
+    /// Cells with one of these tags are excluded.
+    pub skip_tags: Vec<String>,
+    /// Whether line numbers are added to the fenced code blocks.
+    pub line_numbers: bool,
+    /// Whether the conversion stops at the first notebook or file that could
+    /// not be converted, instead of skipping it. It also makes a mismatch of
+    /// the images and the placeholders of [`Command::WrapImage`] an error.
+    pub strict: bool,
+    /// Whether the pages of [`Command::PresenterOnly`] are kept.
+    pub presenter: bool,
+    /// Whether the empty pages, e.g. of consecutive [`Command::NewPage`]
+    /// commands, are dropped. Empty pages with a class are kept.
+    pub trim_empty: bool,
+    /// The profile of the build, which activates the content of the
+    /// matching [`Command::IfProfile`] commands.
+    pub profile: Option<String>,
+    /// Whether the pages without an id of [`Command::PageId`] are numbered
+    /// with the name of the notebook, e.g. `intro-2`.
+    pub auto_id: bool,
+    /// Whether the command comments are kept as ordinary HTML comments on
+    /// the latest page after their commands are executed.
+    pub keep_commands: bool,
+    /// The separator of the slides of a remark presentation, e.g. `***`,
+    /// instead of [`PAGE_SEPARATOR`].
+    pub separator: Option<String>,
+}
+
+impl NotebookOptions {
+    /// Returns the separator of the slides, which is
+    /// [`NotebookOptions::separator`] or [`PAGE_SEPARATOR`] by default.
+    pub fn separator(&self) -> &str {
+        self.separator.as_deref().unwrap_or(PAGE_SEPARATOR)
+    }
+
+    /// Returns `true` if the `page` is kept in the presentation, which drops
+    /// the pages of [`Command::PresenterOnly`] unless
+    /// [`NotebookOptions::presenter`] is set and the empty pages without a
+    /// class if [`NotebookOptions::trim_empty`] is set.
+    fn keeps(&self, page: &Page) -> bool {
+        (self.presenter || !page.presenter_only)
+            && !(self.trim_empty && page.class.is_none() && page.body.trim().is_empty())
+    }
+
+    /// Returns `true` if a cell with the given `tags` is included by
+    /// [`NotebookOptions::only_tags`] and [`NotebookOptions::skip_tags`].
+    /// Tags that are a command are not considered, so cells without other
+    /// tags are always included.
+    fn includes(&self, tags: &[String]) -> bool {
+        let mut keywords = tags
+            .iter()
+            .map(|tag| tag.trim())
+            .filter(|tag| is_keyword_tag(tag))
+            .peekable();
+        if keywords.peek().is_none() {
+            return true;
+        }
+
+        let mut included = self.only_tags.is_empty();
+        for keyword in keywords {
+            if self.skip_tags.iter().any(|tag| tag == keyword) {
+                return false;
+            }
+            included |= self.only_tags.iter().any(|tag| tag == keyword);
+        }
+        included
+    }
+}
+
+/// Trims the leading and trailing whitespace of the content, that a cell
+/// added to the latest page, for [`Command::Trim`]. The `start` holds the
+/// number of pages and the length of the latest page before the cell was
+/// converted, so the content of a page the cell did not start is kept. The
+/// trimmed content keeps a trailing newline, so the content of the next cell
+/// starts on a new line.
+fn trim_added_content(pages: &mut [String], (count, len): (usize, usize)) {
+    let started = pages.len() != count;
+    let Some(last) = pages.last_mut() else {
+        return;
+    };
+    let offset = if started || !last.is_char_boundary(len) {
+        0
+    } else {
+        len
+    };
+    let content = last[offset..].trim().to_string();
+    last.truncate(offset);
+    if !content.is_empty() {
+        last.push_str(&content);
+        last.push('\n');
+    }
+}
+
+/// Returns `true` if the `tag` of a cell is not a command and therefore used
+/// as keyword.
+fn is_keyword_tag(tag: &str) -> bool {
+    matches!(
+        commands::parse(tag),
+        Err(ParseError::UnknownCommand(_) | ParseError::MissingComma(..))
+    )
+}
+
 /// Possible states of a command sequence.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum CommandSequenceState {
@@ -25,13 +158,538 @@ enum CommandSequenceState {
     Outside,
 }
 
+/// The state of a [`Notebook`] that is carried from one [`Cell`] to the next
+/// while the cells are converted to pages.
+#[derive(Debug, Default)]
+struct NotebookState {
+    /// The class that is set, when the next page is initialized or the
+    /// notebook ends.
+    page_class: Option<String>,
+    /// The class that is set for every page, until it is cleared.
+    sticky_page_class: Option<String>,
+    /// The class that is set for every page without a class of
+    /// [`Command::PageClass`], which is read from the metadata of the
+    /// notebook.
+    default_class: Option<String>,
+    /// The variables defined with [`Command::SetVariable`].
+    variables: HashMap<String, String>,
+    /// The keywords of the pages by the index of the page. They are set with
+    /// [`Command::Keywords`] or with cell tags that are not a command.
+    keywords: BTreeMap<usize, Vec<String>>,
+    /// The ids of the pages by the index of the page, which are set with
+    /// [`Command::PageId`].
+    ids: BTreeMap<usize, String>,
+    /// The classes of the pages by the index of the page, which are set by
+    /// [`NotebookState::apply_page_class`].
+    classes: BTreeMap<usize, String>,
+    /// Whether the command comments are kept on the latest page, see
+    /// [`NotebookOptions::keep_commands`].
+    keep_commands: bool,
+    /// Whether a number of images that differs from the placeholders of
+    /// [`Command::WrapImage`] is an error instead of a warning, see
+    /// [`NotebookOptions::strict`].
+    strict: bool,
+    /// The language of the kernel of the notebook, which is used for the
+    /// fenced code blocks of [`Command::Code`].
+    language: Option<String>,
+    /// Whether the lines of the following `markdown` cells are added to the
+    /// latest page. It is set by [`Command::StartAddToPage`] and persists
+    /// across cells until [`Command::StopAddToPage`].
+    add_to_page: bool,
+    /// The canonical paths of the files that are read by
+    /// [`Command::Include`], including the nested ones.
+    included: Vec<PathBuf>,
+    /// The active profile of the build, see [`NotebookOptions::profile`].
+    profile: Option<String>,
+    /// The open [`Command::IfProfile`], until [`Command::EndIf`].
+    profile_block: Option<ProfileBlock>,
+    /// The index of the cell that is converted.
+    cell: usize,
+    /// The indices of the pages, that are only kept for the presenter, see
+    /// [`Command::PresenterOnly`].
+    presenter_only: BTreeSet<usize>,
+}
+
+/// The content of a [`Command::IfProfile`], that is open until
+/// [`Command::EndIf`].
+#[derive(Debug)]
+struct ProfileBlock {
+    /// The profile the content is gated by.
+    profile: String,
+    /// Whether the profile is active, so the content is added.
+    active: bool,
+    /// The cell of the command.
+    cell: usize,
+    /// The line of the command in the cell.
+    line: usize,
+}
+
+impl NotebookState {
+    /// Returns `true` if the content is skipped, because it is gated by a
+    /// profile that is not active.
+    fn skipping(&self) -> bool {
+        self.profile_block
+            .as_ref()
+            .is_some_and(|block| !block.active)
+    }
+
+    /// Sets the class of [`Command::StickyPageClass`] and the class of
+    /// [`Command::PageClass`], or else the default class of the notebook, to
+    /// the latest page. The class of [`Command::PageClass`] is reset
+    /// afterwards.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a class of
+    /// [`Command::PageClass`] is set, but no page was initialized.
+    fn apply_page_class(&mut self, pages: &[String]) -> Result<()> {
+        let class = self.page_class.take();
+        let Some(last) = pages.len().checked_sub(1) else {
+            return match class {
+                Some(_) => Err(uninitialized_page(Command::PAGE_CLASS)),
+                None => Ok(()),
+            };
+        };
+
+        let class = class.or_else(|| self.default_class.clone());
+        let classes = self
+            .sticky_page_class
+            .iter()
+            .chain(class.iter())
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        if !classes.is_empty() {
+            self.classes.insert(last, classes.join(", "));
+        }
+        Ok(())
+    }
+
+    /// Adds the `keywords` to the page at the index `page`. Keywords the page
+    /// already has are skipped.
+    fn add_keywords(&mut self, page: usize, keywords: Vec<String>) {
+        let page_keywords = self.keywords.entry(page).or_default();
+        for keyword in keywords {
+            if !page_keywords.contains(&keyword) {
+                page_keywords.push(keyword);
+            }
+        }
+    }
+
+    /// Replaces all `{{name}}` tokens in the `text` with the value of the
+    /// variable `name`. Tokens of undefined variables are left unchanged. The
+    /// `{{title}}` token is replaced by the title of the presentation, if it
+    /// is not defined.
+    fn substitute_variables(&self, text: &str) -> String {
+        let mut result = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find("{{") {
+            let Some(end) = rest[start..].find("}}").map(|end| start + end) else {
+                break;
+            };
+            let name = rest[(start + 2)..end].trim();
+            result.push_str(&rest[..start]);
+            if let Some(value) = self.variables.get(name) {
+                result.push_str(value);
+            } else {
+                if name != TITLE_VARIABLE {
+                    warn!("Variable '{name}' is not defined and is left unchanged.");
+                }
+                result.push_str(&rest[start..(end + 2)]);
+            }
+            rest = &rest[(end + 2)..];
+        }
+        result.push_str(rest);
+
+        result
+    }
+}
+
+/// The state of a [`Cell`] while its commands are executed.
+#[derive(Debug, Default)]
+struct CellState {
+    /// Whether the lines of the cell are added to the latest page. A
+    /// `markdown` cell starts with the state of the [`NotebookState`].
+    add_to_page: bool,
+    /// The columns that collect the added lines, if defined.
+    columns: Option<ColumnsState>,
+    /// The fragments that collect the added lines, if defined.
+    fragment: Option<FragmentState>,
+    /// The tags of the cell that are not a command. They are added as
+    /// keywords to the latest page after the cell is converted.
+    keywords: Vec<String>,
+    /// Whether the source of the code cell is added to the latest page as
+    /// fenced code block.
+    code: bool,
+    /// Whether the stream outputs of the code cell are added to the latest
+    /// page as fenced block.
+    stream: bool,
+    /// Whether the error outputs of the code cell are added to the latest
+    /// page as fenced block.
+    error: bool,
+    /// Whether the image outputs of the code cell are added to the latest
+    /// page as embedded images.
+    image: bool,
+    /// Whether the source of the raw cell is added to the latest page.
+    raw: bool,
+    /// Whether a new page is started at every `## ` heading of the added
+    /// lines.
+    auto_split: bool,
+    /// Whether the added lines are inside a fenced code block, where headings
+    /// do not start a new page.
+    fenced: bool,
+    /// Whether the content the cell adds to the latest page is trimmed, see
+    /// [`Command::Trim`].
+    trim: bool,
+}
+
+impl CellState {
+    /// Returns `true` if content was added to the latest page, including the
+    /// content collected for the columns or fragments.
+    fn has_content(&self, pages: &[String]) -> bool {
+        pages.last().is_some_and(|page| !page.trim().is_empty())
+            || self
+                .columns
+                .as_ref()
+                .is_some_and(|columns| !columns.content.trim().is_empty())
+            || self
+                .fragment
+                .as_ref()
+                .is_some_and(|fragment| !fragment.content.trim().is_empty())
+    }
+
+    /// Writes the content collected for a [`Command::Fragment`] to the latest
+    /// page, so content of other commands is added in the right order.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no page was initialized.
+    fn flush_fragment(&mut self, pages: &mut [String]) -> Result<()> {
+        match &mut self.fragment {
+            Some(fragment) => fragment.write_to_page(pages),
+            None => Ok(()),
+        }
+    }
+
+    /// Adds the collected keywords of the cell to the latest page. They are
+    /// ignored if no page was initialized.
+    fn add_keywords(self, pages: &[String], notebook: &mut NotebookState) {
+        if self.keywords.is_empty() {
+            return;
+        }
+        if pages.is_empty() {
+            debug!(
+                "The tags {:?} are ignored, because no page was initialized.",
+                self.keywords
+            );
+        } else {
+            notebook.add_keywords(pages.len() - 1, self.keywords);
+        }
+    }
+}
+
+/// Returns `true` if the line starts a top-level markdown list item.
+fn is_list_item(line: &str) -> bool {
+    if line.starts_with("- ") || line.starts_with("* ") || line.starts_with("+ ") {
+        return true;
+    }
+    let rest = line.trim_start_matches(|c: char| c.is_ascii_digit());
+    rest.len() < line.len() && (rest.starts_with(". ") || rest.starts_with(") "))
+}
+
+/// Splits markdown into its top-level list items and paragraphs. Blank lines
+/// and indented lines belong to the preceding fragment and fenced code blocks
+/// are never split.
+fn split_into_fragments(content: &str) -> Vec<String> {
+    let mut fragments: Vec<String> = vec![];
+    let mut in_code_block = false;
+    let mut after_blank_line = true;
+
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim();
+        let is_top_level = !line.starts_with(char::is_whitespace) && !trimmed.is_empty();
+        let starts_fragment =
+            !in_code_block && is_top_level && (after_blank_line || is_list_item(line));
+
+        match fragments.last_mut() {
+            Some(fragment) if !starts_fragment => fragment.push_str(line),
+            _ => fragments.push(line.to_string()),
+        }
+
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        after_blank_line = trimmed.is_empty();
+    }
+
+    fragments
+}
+
+/// Adds the `{.line-numbers}` attribute to the info string of every fenced code
+/// block in the markdown, so the renderer shows line numbers. Code blocks that
+/// already have attributes are left unchanged.
+fn add_line_numbers(markdown: &str) -> String {
+    let mut result = String::new();
+    let mut in_code_block = false;
+
+    for line in markdown.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            if !in_code_block && !trimmed.contains('{') {
+                let indent = &line[..line.len() - line.trim_start().len()];
+                let ending = &line[line.trim_end().len()..];
+                result.push_str(&format!("{indent}{trimmed} {{.line-numbers}}{ending}"));
+            } else {
+                result.push_str(line);
+            }
+            in_code_block = !in_code_block;
+        } else {
+            result.push_str(line);
+        }
+    }
+
+    result
+}
+
+/// The content collected for a [`Command::Fragment`] until it is written to
+/// the latest page.
+#[derive(Debug, Default)]
+struct FragmentState {
+    /// Whether a fragment was already written to the latest page.
+    started: bool,
+    /// The collected content, which is not yet written to the latest page.
+    content: String,
+}
+
+impl FragmentState {
+    /// Splits the collected content into fragments and writes them, separated
+    /// by the incremental separator `--`, to the latest page.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if no page was initialized.
+    fn write_to_page(&mut self, pages: &mut [String]) -> Result<()> {
+        if self.content.trim().is_empty() {
+            self.content.clear();
+            return Ok(());
+        }
+        let Some(last) = pages.last_mut() else {
+            return Err(uninitialized_page(Command::FRAGMENT));
+        };
+
+        for fragment in split_into_fragments(&self.content) {
+            if self.started {
+                last.push_str("--\n");
+            }
+            last.push_str(&fragment);
+            self.started = true;
+        }
+        self.content.clear();
+
+        Ok(())
+    }
+}
+
+/// The content collected for a [`Command::Columns`] until it is written to
+/// the latest page.
+#[derive(Debug)]
+struct ColumnsState {
+    /// The line of the command comment that defined the columns.
+    line: usize,
+    /// The delimiter that separates the left from the right column.
+    delimiter: String,
+    /// The collected content of both columns.
+    content: String,
+}
+
+impl ColumnsState {
+    /// Splits the collected content at the first delimiter and wraps both
+    /// halves in the `.left-column[...]` and `.right-column[...]` markers.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the content does not contain the
+    /// delimiter.
+    fn wrap(&self) -> Result<String> {
+        let Some((left, right)) = self.content.split_once(&self.delimiter) else {
+            return Err(anyhow::Error::msg(format!(
+                "Missing column delimiter '{}' for the columns defined in line {}. ",
+                self.delimiter, self.line
+            )));
+        };
+        Ok(format!(
+            ".left-column[\n{}\n]\n.right-column[\n{}\n]\n",
+            left.trim_matches('\n'),
+            right.trim_matches('\n')
+        ))
+    }
+
+    /// Writes the wrapped columns to the latest page.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the delimiter is missing or no
+    /// page was initialized.
+    fn write_to_page(&self, pages: &mut [String]) -> Result<()> {
+        let wrap = self.wrap()?;
+        if let Some(last) = pages.last_mut() {
+            *last = format!("{last}{wrap}");
+            Ok(())
+        } else {
+            Err(uninitialized_page(Command::COLUMNS))
+        }
+    }
+}
+
+/// Reads the file of a [`Command::Include`], where `include` is the path
+/// relative to the `including_path`. Include commands inside the file are
+/// resolved recursively and all relative paths are prefixed, so they are
+/// relative to the including file. `includes` holds the files that are
+/// currently included to detect recursive includes, and every file that is
+/// read is added to `included`.
+///
+/// # Errors
+///
+/// This function will return an error if:
+/// - The file could not be read.
+/// - The file includes itself directly or indirectly.
+/// - The file contains a command other than [`Command::Include`].
+fn read_include(
+    including_path: &Path,
+    include: &str,
+    includes: &mut Vec<PathBuf>,
+    included: &mut Vec<PathBuf>,
+) -> Result<String> {
+    let path = including_path
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(include);
+    let canonical_path = fs::canonicalize(&path)
+        .map_err(|err| anyhow::Error::msg(format!("Unable to include {path:?}. {err}")))?;
+    if includes.contains(&canonical_path) {
+        return Err(anyhow::Error::msg(format!(
+            "Recursive include of {path:?}. "
+        )));
+    }
+    let text = fs::read_to_string(&path)
+        .map_err(|err| anyhow::Error::msg(format!("Unable to include {path:?}. {err}")))?;
+
+    if !included.contains(&canonical_path) {
+        included.push(canonical_path.clone());
+    }
+    includes.push(canonical_path);
+    let mut content = String::new();
+    for line in text.split_inclusive('\n') {
+        let trimmed = line.trim();
+        if !(trimmed.starts_with("<!--!") && trimmed.ends_with("-->")) {
+            content.push_str(line);
+            continue;
+        }
+
+        let commands = commands::parse(&trimmed[5..(trimmed.len() - 3)]).map_err(|err| {
+            anyhow::Error::msg(format!("Unable to parse commands in {path:?}. {err}"))
+        })?;
+        for command in commands {
+            match command {
+                Command::Include(include) => {
+                    content.push_str(&read_include(&path, &include, includes, included)?);
+                }
+                command => {
+                    return Err(anyhow::Error::msg(format!(
+                        "Only the '{}' command is supported in the included file {path:?}, but found {command:?}. ",
+                        Command::INCLUDE
+                    )))
+                }
+            }
+        }
+    }
+    includes.pop();
+
+    let prefix = Path::new(include).parent().unwrap_or_else(|| Path::new(""));
+    Ok(prefix_paths(prefix, content))
+}
+
+/// Returns the error for the command `name`, that is used before a page was
+/// initialized.
+fn uninitialized_page(name: &str) -> anyhow::Error {
+    anyhow::Error::msg(format!(
+        "Command `{name}` used before a page was initialized (add `{};` first). ",
+        Command::NEW_PAGE
+    ))
+}
+
+/// Adds the `content` as fenced block with the `language` to the latest page
+/// for the command `name`.
+///
+/// # Errors
+///
+/// This function will return an error if no page was initialized.
+fn add_fenced_block(
+    pages: &mut [String],
+    name: &str,
+    language: &str,
+    mut content: String,
+) -> Result<()> {
+    let Some(last) = pages.last_mut() else {
+        return Err(uninitialized_page(name));
+    };
+    if !content.ends_with('\n') {
+        content.push('\n');
+    }
+    *last = format!("{last}```{language}\n{content}```\n");
+    Ok(())
+}
+
+/// Returns the page at the `offset` relative to the latest page, where `0` is
+/// the latest page and `-1` the page before. Returns `None` if the page does
+/// not exist.
+fn relative_page(pages: &mut [String], offset: isize) -> Option<&mut String> {
+    let index = pages.len().checked_sub(1)?.checked_add_signed(offset)?;
+    pages.get_mut(index)
+}
 
 /// This struct represents the metadata of a notebook cell. The `tags` property
 /// is used to execute the commands defined by the tags.
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 struct Metadata {
     /// The tags in a cell represented as `String`
     tags: Option<Vec<String>>,
+    /// The slideshow metadata of `RISE` and `nbconvert`.
+    slideshow: Option<Slideshow>,
+}
+
+/// The slideshow metadata of a notebook cell, as written by `RISE` and
+/// `nbconvert`.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Slideshow {
+    /// The slide type of the cell: `slide`, `subslide`, `fragment`, `notes`,
+    /// `skip` or `-` to continue the current slide.
+    slide_type: Option<String>,
+}
+
+/// The kernel specification of a notebook, including only the necessary
+/// properties.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct KernelSpec {
+    /// The programming language of the kernel, e.g. `python`.
+    language: Option<String>,
+}
+
+/// The options of the presentation in the metadata of a notebook.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct PresentationMetadata {
+    /// The class of every page without a class of [`Command::PageClass`].
+    default_class: Option<String>,
+}
+
+/// This struct represents the metadata of a whole notebook. Relevant are only
+/// the language of the kernel and the options of the presentation.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct NotebookMetadata {
+    /// The kernel the notebook was written for.
+    kernelspec: Option<KernelSpec>,
+    /// The options of the presentation, e.g. the default class.
+    presentation: Option<PresentationMetadata>,
 }
 
 /// The output types of a notebook cell, including only the
@@ -51,6 +709,12 @@ enum Output {
         /// The content of the stream.
         text: Vec<String>,
     },
+    /// Output of a cell that displays data, e.g. a plot.
+    DisplayData {
+        /// The displayed data by its mime type. Images are base64 encoded,
+        /// either as single string or split into lines.
+        data: HashMap<String, serde_json::Value>,
+    },
     /// Every other output type, wich are ignored in this program. Needs to be
     /// defined to withheld errors caused by `serde` not finding a fitting
     /// enum variant to parse to.
@@ -63,39 +727,178 @@ enum Output {
 struct Cell {
     /// Type of the cell (e.g. Markdown or code)
     cell_type: String,
-    // TODO Remove Metadata since it has no use anymore
-    /// The metadata bounded to the cell. Relevant are only the tags.
+    /// The metadata bounded to the cell. Relevant are only the tags. Some
+    /// converters omit it.
+    #[serde(default)]
     metadata: Metadata,
     /// Possible outputs of a cell, e.g. an error of a code cell.
     outputs: Option<Vec<Output>>,
-    /// The content of a cell.
+    /// The files attached to a markdown cell by their name, each holding the
+    /// data by its mime type.
+    attachments: Option<HashMap<String, HashMap<String, serde_json::Value>>>,
+    /// The content of a cell, one string per line. A single string is split
+    /// into its lines.
+    #[serde(deserialize_with = "deserialize_source")]
     source: Vec<String>,
 }
+
+/// The source of a [`Cell`] as it is stored in the notebook, which some tools
+/// write as a single string instead of one string per line.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Source {
+    /// The source split into lines.
+    Lines(Vec<String>),
+    /// The whole source in one string.
+    Text(String),
+}
+
+/// Deserializes the source of a [`Cell`] with the line endings `\r\n` and `\r`
+/// of notebooks authored on Windows normalized to `\n`, split into one string
+/// per line. The source may be a list of lines or a single string. Every
+/// line of a list but the last ends with `\n`, even if the notebook stores
+/// the lines without it.
+fn deserialize_source<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Vec<String>, D::Error> {
+    let source = match Source::deserialize(deserializer)? {
+        Source::Lines(lines) => {
+            let count = lines.len();
+            lines
+                .into_iter()
+                .enumerate()
+                .map(|(i, line)| {
+                    let line = line.replace("\r\n", "\n").replace('\r', "\n");
+                    if i + 1 < count && !line.ends_with('\n') {
+                        format!("{line}\n")
+                    } else {
+                        line
+                    }
+                })
+                .collect()
+        }
+        Source::Text(text) => text,
+    };
+    Ok(source
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .split_inclusive('\n')
+        .map(str::to_string)
+        .collect())
+}
+
+/// Returns the richest image of the `data` by its mime type as `data:` url, or
+/// `None` if it holds no image.
+fn image_url(data: &HashMap<String, serde_json::Value>) -> Option<String> {
+    IMAGE_MIME_TYPES.iter().find_map(|mime| {
+        let base64 = match data.get(*mime)? {
+            serde_json::Value::String(base64) => base64.clone(),
+            serde_json::Value::Array(lines) => lines
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .collect::<String>(),
+            _ => return None,
+        };
+        let base64 = base64.split_whitespace().collect::<String>();
+        Some(format!("data:{mime};base64,{base64}"))
+    })
+}
+
+impl Output {
+    /// Returns the richest image of this [`Output`] as `data:` url, or `None`
+    /// if it holds no image.
+    fn image_url(&self) -> Option<String> {
+        match self {
+            Output::DisplayData { data } => image_url(data),
+            _ => None,
+        }
+    }
+}
+
 impl Cell {
-    /// Returns the get source without commands comment of this [`Cell`].
+    /// Replaces the `attachment:` references to the attachments of this
+    /// [`Cell`] in its source with `data:` urls. Attachments that are not an
+    /// image are left unchanged.
+    fn resolve_attachments(&mut self) {
+        let Some(attachments) = &self.attachments else {
+            return;
+        };
+        let mut names = attachments.keys().collect::<Vec<_>>();
+        // Longer names first, so a name is not replaced inside a longer one.
+        names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+        for name in names {
+            let Some(url) = image_url(&attachments[name]) else {
+                warn!("The attachment '{name}' is not a supported image.");
+                continue;
+            };
+            let reference = format!("attachment:{name}");
+            for line in &mut self.source {
+                if line.contains(&reference) {
+                    *line = line.replace(&reference, &url);
+                }
+            }
+        }
+    }
+
+    /// Returns the trimmed `line` of this [`Cell`] for finding command
+    /// comments. In a `code` cell the command comments are written inside
+    /// line comments, e.g. `# <!--! code -->`, so the comment prefix is
+    /// removed.
+    fn trim_line<'a>(&self, line: &'a str) -> &'a str {
+        let trimmed = line.trim();
+        if self.cell_type != "code" {
+            return trimmed;
+        }
+        trimmed
+            .strip_prefix('#')
+            .or_else(|| trimmed.strip_prefix("//"))
+            .map_or(trimmed, str::trim_start)
+    }
+
+    /// Returns the get source without commands comment of this [`Cell`]. Only
+    /// the span of a comment is removed, so the text before `<!--!` and after
+    /// `-->` on the same line is kept. Lines that only hold a comment are
+    /// removed completely.
     ///
     /// # Errors
     ///
     /// This function will return an error if the cell type is not `markdown` or `code`.
     fn get_source_without_commands_comment(&self) -> Result<String> {
         match self.cell_type.as_str() {
-            "markdown" => {
+            "markdown" | "code" => {
                 let mut is_command = false;
-                let text = self
-                    .source
-                    .iter()
-                    .filter(|f| {
-                        let trimmed = f.trim();
-                        if trimmed.starts_with("<!--!") {
+                let mut text = String::new();
+                for line in &self.source {
+                    let mut has_command = is_command;
+                    let mut kept = String::new();
+                    let mut rest = line.as_str();
+                    loop {
+                        if is_command {
+                            let Some(end) = rest.find("-->") else {
+                                break;
+                            };
+                            rest = &rest[(end + 3)..];
+                            is_command = false;
+                        } else if let Some(start) = rest.find("<!--!") {
+                            kept.push_str(&rest[..start]);
+                            rest = &rest[(start + 5)..];
                             is_command = true;
+                            has_command = true;
+                        } else {
+                            kept.push_str(rest);
+                            break;
                         }
-                        if trimmed.ends_with("-->") {
-                            is_command = false;
+                    }
+
+                    if !has_command {
+                        text.push_str(line);
+                    } else if !self.trim_line(&kept).is_empty() {
+                        text.push_str(&kept);
+                        if line.ends_with('\n') && !kept.ends_with('\n') {
+                            text.push('\n');
                         }
-                        !is_command
-                    })
-                    .cloned()
-                    .collect::<String>();
+                    }
+                }
                 Ok(text)
             }
             _ => Err(anyhow::Error::msg(format!(
@@ -105,208 +908,877 @@ impl Cell {
         }
     }
 
-    /// Processes the current [`Cell`] and executes all contained commands. The
-    /// contents of the cell are read line by line, and any command comments
-    /// encountered are collected until the comment ends. All collected
-    /// commands are then executed in the order they were encountered, except
-    /// for [`Command::PageClass`].
+    /// Parses the tags of the current [`Cell`] and executes the contained
+    /// commands. A tag is handled like a command comment with a single
+    /// command, so the trailing `;` is optional. Tags that are not a command
+    /// are collected as keywords of the latest page.
     ///
     /// # Errors
     ///
-    /// This function may return an error if:
-    /// - The cell type is not `markdown` or `code`.
-    /// - The command comment cannot be parsed.
-    /// - The [`Command::StartAddToPage`], [`Command::AddStreamToPage`],
-    /// [`Command::AddErrorToPage`], [`Command::InjectToPage`],
-    /// [`Command::WrapImage`], and [`Command::PageClass`] commands are used
-    /// before a page is initialized.
-    /// - The `markdown` command comment is not properly closed.
-    fn proses_to_presentation(
+    /// This function will return an error if a tag is a badly formatted
+    /// command or the command could not be executed.
+    fn execute_tags(
         &self,
+        path: &Path,
         pages: &mut Vec<String>,
-        page_class: &mut Option<String>,
+        notebook: &mut NotebookState,
+        state: &mut CellState,
     ) -> Result<()> {
-        match self.cell_type.as_str() {
-            "markdown" => (),
-            cell_type => {
-                debug!("Cell type: '{cell_type}' is currently not supported.");
-                return Ok(());
-            }
+        let Some(tags) = &self.metadata.tags else {
+            return Ok(());
+        };
+
+        for tag in tags {
+            let tag = tag.trim();
+            let commands = match commands::parse(tag) {
+                Ok(commands) => commands,
+                Err(ParseError::UnknownCommand(_) | ParseError::MissingComma(..)) => {
+                    debug!("Tag '{tag}' is not a command and is used as keyword.");
+                    if !notebook.skipping() {
+                        state.keywords.push(tag.to_string());
+                    }
+                    continue;
+                }
+                Err(err) => {
+                    return Err(anyhow::Error::msg(format!(
+                        "Unable to parse tag '{tag}'. {err}"
+                    )))
+                }
+            };
+
+            debug!("{commands:?}");
+            self.execute_commands(commands, 0, path, pages, notebook, state)
+                .map_err(|op| {
+                    let text = format!("Tag '{tag}'. {op}");
+                    op.context(text)
+                })?;
         }
 
-        let mut command_line = 0;
-        let mut command_sequence_state = CommandSequenceState::Outside;
-        let mut command_sequence = vec![];
-        let mut add_to_page = false;
+        Ok(())
+    }
 
-        let mut lines = self.source.iter().enumerate().peekable();
-        while let Some((i, line)) = lines.next() {
-            if command_sequence_state == CommandSequenceState::Outside {
-                command_line = i;
+    /// Translates the `slide_type` of the slideshow metadata of the current
+    /// [`Cell`] to commands and executes them. `slide` and `subslide` start a
+    /// new page, `fragment` adds an incremental separator `--` and `notes`
+    /// starts the speaker notes `???` of the latest page. Afterwards the
+    /// content of the cell is added, for a `code` cell with [`Command::Code`].
+    /// `-` only adds the content if a page is initialized.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the slide type is unknown or
+    /// the commands could not be executed.
+    fn execute_slide_type(
+        &self,
+        slide_type: &str,
+        path: &Path,
+        pages: &mut Vec<String>,
+        notebook: &mut NotebookState,
+        state: &mut CellState,
+    ) -> Result<()> {
+        let mut commands = match slide_type {
+            "slide" | "subslide" => vec![Command::NewPage],
+            "fragment" => vec![Command::InjectToPage("--\n".to_string())],
+            "notes" => match pages.last() {
+                Some(last) if last.lines().any(|line| line.trim() == "???") => vec![],
+                _ => vec![Command::InjectToPage("???\n".to_string())],
+            },
+            "-" if pages.is_empty() => return Ok(()),
+            "-" => vec![],
+            slide_type => {
+                return Err(anyhow::Error::msg(format!(
+                    "Unknown slide type '{slide_type}'. "
+                )))
             }
+        };
+        if self.cell_type == "code" {
+            commands.push(Command::Code);
+        } else {
+            commands.push(Command::StartAddToPage);
+        }
 
-            let trimmed = line.trim();
-            match (trimmed.starts_with("<!--!"), trimmed.ends_with("-->")) {
-                (true, true) => {
-                    command_sequence_state = CommandSequenceState::End;
-                    command_sequence.push(&trimmed[5..(trimmed.len() - 3)]);
+        debug!("{commands:?}");
+        self.execute_commands(commands, 0, path, pages, notebook, state)
+    }
+
+    /// Executes the given commands of the current [`Cell`] in order. `line`
+    /// is the line of the command comment the commands were defined in and
+    /// `path` the path of the notebook.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if a command that needs a page is
+    /// used before a page is initialized or the content of a command could
+    /// not be applied.
+    fn execute_commands(
+        &self,
+        commands: Vec<Command>,
+        line: usize,
+        path: &Path,
+        pages: &mut Vec<String>,
+        notebook: &mut NotebookState,
+        state: &mut CellState,
+    ) -> Result<()> {
+        for command in commands {
+            if notebook.skipping() && !matches!(command, Command::IfProfile(_) | Command::EndIf) {
+                continue;
+            }
+            match command {
+                Command::NewPage => {
+                    if let Some(columns) =
+                        state.columns.take_if(|columns| !columns.content.is_empty())
+                    {
+                        columns.write_to_page(pages)?;
+                    }
+                    state.flush_fragment(pages)?;
+                    if let Some(fragment) = &mut state.fragment {
+                        fragment.started = false;
+                    }
+                    notebook.apply_page_class(pages)?;
+                    pages.push(String::new());
                 }
-                (true, false) => {
-                    command_sequence_state = CommandSequenceState::Within;
-                    command_sequence.push(&trimmed[5..trimmed.len()]);
+                Command::StartAddToPage => {
+                    state.add_to_page = true;
+                    notebook.add_to_page = true;
                 }
-                (false, true) => {
-                    if command_sequence_state == CommandSequenceState::Within {
-                        command_sequence_state = CommandSequenceState::End;
-                        command_sequence.push(&trimmed[0..(trimmed.len() - 3)]);
+                Command::StopAddToPage => {
+                    state.add_to_page = false;
+                    notebook.add_to_page = false;
+                    if let Some(columns) = state.columns.take() {
+                        columns.write_to_page(pages)?;
                     }
+                    state.flush_fragment(pages)?;
+                    state.fragment = None;
                 }
-                (false, false) => {
-                    if command_sequence_state == CommandSequenceState::Within {
-                        command_sequence.push(trimmed);
+                Command::InjectToPage(content) => {
+                    state.flush_fragment(pages)?;
+                    if let Some(last) = relative_page(pages, 0) {
+                        *last = format!("{last}{}", notebook.substitute_variables(&content));
+                    } else {
+                        return Err(uninitialized_page(Command::INJECT_TP_PAGE));
                     }
                 }
-            };
-
-            || -> Result<()> {
-                if command_sequence_state == CommandSequenceState::Outside || lines.peek().is_none() {
-                    let stream = command_sequence.join("");
-                    let stream = stream.trim();
-                    if !stream.is_empty() {
-
-                        let commands = commands::parse(stream)
-                        .map_err(|err| {
-                            anyhow::Error::msg(format!("Unable to parse commands. '{}' {} ", command_sequence.join("").trim(), err))
-                        })?;
-                        
-                        debug!("{commands:?}");
-                        
-                        for command in commands {
-                            match command {
-                                Command::NewPage => {
-                                    if let Some(class) = page_class {
-                                        if let Some(last) = pages.last_mut() {
-                                            *last = format!("class: {class}\n\n{last}");
-                                            *page_class = None;
-                                        } else {
-                                            return Err(anyhow::Error::msg(
-                                                "Tried to set a class page that was not initialized. ",
-                                            ));
-                                        }
-                                    }
-                                    pages.push(String::new());
-                                },
-                            Command::StartAddToPage => {
-                                add_to_page = true;
-                                },
-                            Command::StopAddToPage => {
-                                add_to_page = false;
-                            },
-                            Command::InjectToPage(content) => {
-                                if let Some(last) = pages.last_mut() {
-                                    *last = format!("{last}{}", content);
-                                } else {
-                                    return Err(anyhow::Error::msg(
-                                        format!("Tried to insert '{content}' to a page that was not initialized. "),
-                                    ));
-                                }
-                            }
-                            Command::WrapImage(content) => {
-                                if let Some(last) = pages.last_mut() {
-                                    let wrap = wrap_image(
-                                        &self.get_source_without_commands_comment()?,
-                                        &content,
-                                    )?;
-                                    *last = format!("{last}{}", wrap);
-                                } else {
-                                    return Err(anyhow::Error::msg(
-                                        "Tried to insert a 'WrapImage' to a page that was not initialized. ".to_string(),
-                                    ));
-                                }
-                            }
-                            Command::PageClass(class) => *page_class = Some(class),
-                        }
+                Command::Html(html) => {
+                    state.flush_fragment(pages)?;
+                    let Some(last) = pages.last_mut() else {
+                        return Err(uninitialized_page(Command::HTML));
+                    };
+                    last.push_str(&html);
+                    if !html.ends_with('\n') {
+                        last.push('\n');
                     }
+                }
+                Command::InjectTop(content) => {
+                    let Some(last) = pages.last_mut() else {
+                        return Err(uninitialized_page(Command::INJECT_TOP));
+                    };
+                    // The properties of the page stay in front of the content.
+                    let mut page = Page::from_markdown(last);
+                    let mut content = notebook.substitute_variables(&content);
+                    if !content.ends_with('\n') && !page.body.is_empty() {
+                        content.push('\n');
                     }
-                    command_sequence.clear()
+                    page.body = format!("{content}{}", page.body);
+                    *last = page.to_string();
                 }
-                    if add_to_page && command_sequence_state == CommandSequenceState::Outside{
-                        if let Some(last) = pages.last_mut() {
-                            if line.ends_with('\n'){
-                                *last = format!("{last}{}", line.clone());
-                            }else{
-                                *last = format!("{last}{}\n", line.clone());
-                            }
-                        } else {
-                            return Err(anyhow::Error::msg(
-                                "Tried to insert to a page that was not initialized. "
-                                .to_string(),
-                            ));
-                        }
+                Command::InjectAtPage(offset, content) => {
+                    let len = pages.len();
+                    if let Some(page) = relative_page(pages, offset) {
+                        *page = format!("{page}{}", notebook.substitute_variables(&content));
+                    } else {
+                        return Err(anyhow::Error::msg(format!(
+                            "Tried to insert '{content}' to the page at offset {offset}, but only {len} pages are initialized. "
+                        )));
                     }
-
-                Ok(())
-            }().map_err(|op| {
-                let text = format!("Line {command_line} to {i}. {}",op);
-                op.context(text)
+                }
+                Command::WrapImage(content) => {
+                    state.flush_fragment(pages)?;
+                    if let Some(last) = pages.last_mut() {
+                        let wrap = wrap_image(
+                            &self.get_source_without_commands_comment()?,
+                            &content,
+                            notebook.strict,
+                        )?;
+                        *last = format!("{last}{}", wrap);
+                    } else {
+                        return Err(uninitialized_page(Command::WRAP_IMAGE));
+                    }
+                }
+                Command::PageClass(class) => notebook.page_class = Some(class),
+                Command::ClearClass => notebook.page_class = None,
+                Command::StickyPageClass(class) if class.is_empty() => {
+                    notebook.sticky_page_class = None
+                }
+                Command::StickyPageClass(class) => notebook.sticky_page_class = Some(class),
+                Command::Keywords(keywords) => {
+                    if pages.is_empty() {
+                        return Err(uninitialized_page(Command::PAGE_KEYWORDS));
+                    }
+                    notebook.add_keywords(pages.len() - 1, keywords);
+                }
+                Command::PageId(id) => {
+                    if pages.is_empty() {
+                        return Err(uninitialized_page(Command::PAGE_ID));
+                    }
+                    notebook.ids.insert(pages.len() - 1, id);
+                }
+                Command::PresenterOnly => {
+                    if pages.is_empty() {
+                        return Err(uninitialized_page(Command::PRESENTER_ONLY));
+                    }
+                    notebook.presenter_only.insert(pages.len() - 1);
+                }
+                Command::Columns(delimiter) => {
+                    state.columns = Some(ColumnsState {
+                        line,
+                        delimiter,
+                        content: String::new(),
+                    });
+                }
+                Command::Include(include) => {
+                    state.flush_fragment(pages)?;
+                    if let Some(last) = pages.last_mut() {
+                        let content =
+                            read_include(path, &include, &mut vec![], &mut notebook.included)?;
+                        *last = format!("{last}{}", notebook.substitute_variables(&content));
+                    } else {
+                        return Err(uninitialized_page(Command::INCLUDE));
+                    }
+                }
+                Command::SetVariable(name, value) => {
+                    notebook.variables.insert(name, value);
+                }
+                Command::IfProfile(profile) => {
+                    if let Some(block) = &notebook.profile_block {
+                        return Err(anyhow::Error::msg(format!(
+                            "The command `{}[{profile}]` is nested in `{}[{}]`, but nesting is not supported. ",
+                            Command::IF_PROFILE,
+                            Command::IF_PROFILE,
+                            block.profile
+                        )));
+                    }
+                    notebook.profile_block = Some(ProfileBlock {
+                        active: notebook.profile.as_ref() == Some(&profile),
+                        profile,
+                        cell: notebook.cell,
+                        line,
+                    });
+                }
+                Command::EndIf => {
+                    if notebook.profile_block.take().is_none() {
+                        return Err(anyhow::Error::msg(format!(
+                            "The command `{}` has no matching `{}`. ",
+                            Command::END_IF,
+                            Command::IF_PROFILE
+                        )));
+                    }
+                }
+                Command::Fragment => {
+                    state.flush_fragment(pages)?;
+                    state.fragment = Some(FragmentState::default());
+                }
+                Command::AutoSplit => state.auto_split = true,
+                Command::Trim => state.trim = true,
+                Command::Code
+                | Command::AddStreamToPage
+                | Command::AddErrorToPage
+                | Command::AddImageToPage
+                    if self.cell_type != "code" =>
+                {
+                    return Err(anyhow::Error::msg(format!(
+                        "The '{}', '{}', '{}' and '{}' commands can only be used in a code cell. ",
+                        Command::CODE,
+                        Command::ADD_STREAM_TO_PAGE,
+                        Command::ADD_ERROR_TO_PAGE,
+                        Command::ADD_IMAGE_TO_PAGE
+                    )));
+                }
+                Command::Code => state.code = true,
+                Command::AddStreamToPage => state.stream = true,
+                Command::AddErrorToPage => state.error = true,
+                Command::AddImageToPage => state.image = true,
+                Command::Raw if self.cell_type != "raw" => {
+                    return Err(anyhow::Error::msg(format!(
+                        "The '{}' command can only be used in a raw cell. ",
+                        Command::RAW
+                    )));
+                }
+                Command::Raw => state.raw = true,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Processes the current [`Cell`] and executes all contained commands.
+    /// Cells with the slide type `skip` are ignored. The slide type and
+    /// afterwards the commands of the cell tags are executed first. The
+    /// source of a `raw` cell is not parsed, but added to the latest page as
+    /// it is, if [`Command::StartAddToPage`] or [`Command::Raw`] is set. Afterwards the
+    /// contents of the cell are read line by line, and any command comments
+    /// encountered are collected until the comment ends. All collected
+    /// commands are then executed in the order they were encountered, except
+    /// for [`Command::PageClass`]. The source of a `code` cell with
+    /// [`Command::Code`] and its outputs with [`Command::AddStreamToPage`],
+    /// [`Command::AddErrorToPage`] and [`Command::AddImageToPage`] are added
+    /// to the latest page at last.
+    ///
+    /// # Errors
+    ///
+    /// This function may return an error if:
+    /// - The cell type is not `markdown` or `code`.
+    /// - The command comment or a command tag cannot be parsed.
+    /// - The [`Command::StartAddToPage`], [`Command::AddStreamToPage`],
+    ///   [`Command::AddErrorToPage`], [`Command::InjectToPage`],
+    ///   [`Command::InjectAtPage`],
+    ///   [`Command::WrapImage`], [`Command::PageClass`],
+    ///   [`Command::Columns`], [`Command::Include`] and [`Command::Fragment`]
+    ///   commands are used before a page is initialized.
+    /// - The `markdown` command comment is not properly closed.
+    /// - The content after a [`Command::Columns`] has no delimiter.
+    /// - The file of a [`Command::Include`] could not be included.
+    /// - The [`Command::Code`], [`Command::AddStreamToPage`],
+    ///   [`Command::AddErrorToPage`] or [`Command::AddImageToPage`] is used
+    ///   outside of a `code` cell or before a page is initialized.
+    /// - The [`Command::Raw`] is used outside of a `raw` cell or before a page
+    ///   is initialized.
+    fn proses_to_presentation(
+        &self,
+        path: &Path,
+        pages: &mut Vec<String>,
+        notebook: &mut NotebookState,
+    ) -> Result<()> {
+        match self.cell_type.as_str() {
+            "markdown" | "code" | "raw" => (),
+            cell_type => {
+                debug!("Cell type: '{cell_type}' is currently not supported.");
+                return Ok(());
+            }
+        }
+
+        let mut state = CellState {
+            add_to_page: notebook.add_to_page && self.cell_type == "markdown",
+            ..Default::default()
+        };
+        let start = (pages.len(), pages.last().map_or(0, String::len));
+        let slide_type = self
+            .metadata
+            .slideshow
+            .as_ref()
+            .and_then(|slideshow| slideshow.slide_type.as_deref());
+        if slide_type == Some("skip") {
+            debug!("Skip cell because of its slide type.");
+            return Ok(());
+        }
+        if let Some(slide_type) = slide_type {
+            self.execute_slide_type(slide_type, path, pages, notebook, &mut state)
+                .map_err(|op| {
+                    let text = format!("Slide type '{slide_type}'. {op}");
+                    op.context(text)
+                })?;
+        }
+        self.execute_tags(path, pages, notebook, &mut state)?;
+
+        if self.cell_type == "raw" {
+            if (state.add_to_page || state.raw) && !notebook.skipping() {
+                let Some(last) = pages.last_mut() else {
+                    let name = if state.raw {
+                        Command::RAW
+                    } else {
+                        Command::START_ADD_TO_PAGE
+                    };
+                    return Err(uninitialized_page(name));
+                };
+                let raw = self.source.concat();
+                if raw.ends_with('\n') {
+                    *last = format!("{last}{raw}");
+                } else {
+                    *last = format!("{last}{raw}\n");
+                }
+            }
+            if state.trim {
+                trim_added_content(pages, start);
+            }
+            state.add_keywords(pages, notebook);
+            return Ok(());
+        }
+
+        let mut command_line = 0;
+        let mut command_sequence_state = CommandSequenceState::Outside;
+        let mut command_sequence = vec![];
+
+        let mut lines = self.source.iter().enumerate().peekable();
+        while let Some((i, line)) = lines.next() {
+            if command_sequence_state == CommandSequenceState::Outside
+                && command_sequence.is_empty()
+            {
+                command_line = i;
+            }
+
+            let trimmed = self.trim_line(line);
+            match (trimmed.starts_with("<!--!"), trimmed.ends_with("-->")) {
+                (true, true) => {
+                    command_sequence_state = CommandSequenceState::End;
+                    command_sequence.push(&trimmed[5..(trimmed.len() - 3)]);
+                }
+                (true, false) => {
+                    command_sequence_state = CommandSequenceState::Within;
+                    command_sequence.push(&trimmed[5..trimmed.len()]);
+                }
+                (false, true) => {
+                    if command_sequence_state == CommandSequenceState::Within {
+                        command_sequence_state = CommandSequenceState::End;
+                        command_sequence.push(&trimmed[0..(trimmed.len() - 3)]);
+                    }
+                }
+                (false, false) => {
+                    if command_sequence_state == CommandSequenceState::Within {
+                        command_sequence.push(trimmed);
+                    }
+                }
+            };
+
+            let is_last_line = lines.peek().is_none();
+            || -> Result<()> {
+                if is_last_line && command_sequence_state == CommandSequenceState::Within {
+                    return Err(anyhow::Error::msg(format!(
+                        "Missing comment closing element of the comment opened at line {command_line}. "
+                    )));
+                }
+                if command_sequence_state == CommandSequenceState::Outside || is_last_line {
+                    let stream = command_sequence.join("");
+                    let stream = stream.trim();
+                    if !stream.is_empty() {
+                        let commands = commands::parse(stream).map_err(|error| CommandError {
+                            stream: stream.to_string(),
+                            error,
+                        })?;
+
+                        debug!("{commands:?}");
+
+                        self.execute_commands(
+                            commands,
+                            command_line,
+                            path,
+                            pages,
+                            notebook,
+                            &mut state,
+                        )?;
+                        if notebook.keep_commands && !notebook.skipping() {
+                            if let Some(last) = pages.last_mut() {
+                                last.push_str(&format!("<!-- {stream} -->\n"));
+                            }
+                        }
+                    }
+                    command_sequence.clear()
+                }
+
+                if state.add_to_page
+                    && !notebook.skipping()
+                    && command_sequence_state == CommandSequenceState::Outside
+                {
+                    if state.auto_split {
+                        if line.trim_start().starts_with("```") {
+                            state.fenced = !state.fenced;
+                        } else if !state.fenced
+                            && line.starts_with("## ")
+                            && state.has_content(pages)
+                        {
+                            self.execute_commands(
+                                vec![Command::NewPage],
+                                i,
+                                path,
+                                pages,
+                                notebook,
+                                &mut state,
+                            )?;
+                        }
+                    }
+                    let line = notebook.substitute_variables(line);
+                    if let Some(columns) = &mut state.columns {
+                        columns.content.push_str(&line);
+                        if !line.ends_with('\n') {
+                            columns.content.push('\n');
+                        }
+                    } else if let Some(fragment) = &mut state.fragment {
+                        fragment.content.push_str(&line);
+                        if !line.ends_with('\n') {
+                            fragment.content.push('\n');
+                        }
+                    } else if let Some(last) = pages.last_mut() {
+                        if line.ends_with('\n') {
+                            *last = format!("{last}{line}");
+                        } else {
+                            *last = format!("{last}{line}\n");
+                        }
+                    } else {
+                        return Err(uninitialized_page(Command::START_ADD_TO_PAGE));
+                    }
+                }
+
+                Ok(())
+            }()
+            .map_err(|op| {
+                let text = format!("Line {command_line} to {i}. {}", op);
+                op.context(text)
             })?;
-            
+
             if command_sequence_state == CommandSequenceState::End {
                 command_sequence_state = CommandSequenceState::Outside;
             }
         }
 
-        if command_sequence_state == CommandSequenceState::Within {
-            return Err(anyhow::Error::msg(
-                "Missing comment closing element. ".to_string(),
-            ));
+        if let Some(columns) = state.columns.take() {
+            columns.write_to_page(pages).map_err(|op| {
+                let text = format!(
+                    "Line {} to {}. {}",
+                    columns.line,
+                    self.source.len().saturating_sub(1),
+                    op
+                );
+                op.context(text)
+            })?;
+        }
+        state.flush_fragment(pages).map_err(|op| {
+            let text = format!(
+                "Line {} to {}. {}",
+                command_line,
+                self.source.len().saturating_sub(1),
+                op
+            );
+            op.context(text)
+        })?;
+        if state.code {
+            let language = notebook.language.as_deref().unwrap_or(DEFAULT_LANGUAGE);
+            let code = self.get_source_without_commands_comment()?;
+            add_fenced_block(pages, Command::CODE, language, code)?;
+        }
+        if state.stream {
+            let stream = self
+                .outputs
+                .iter()
+                .flatten()
+                .filter_map(|output| match output {
+                    Output::Stream { text } => Some(text.concat()),
+                    _ => None,
+                })
+                .collect::<String>();
+            if !stream.is_empty() {
+                add_fenced_block(pages, Command::ADD_STREAM_TO_PAGE, "", stream)?;
+            }
+        }
+        if state.error {
+            let errors = self
+                .outputs
+                .iter()
+                .flatten()
+                .filter_map(|output| match output {
+                    Output::Error { ename, evalue } => Some(format!("{ename}: {evalue}\n")),
+                    _ => None,
+                })
+                .collect::<String>();
+            if !errors.is_empty() {
+                add_fenced_block(pages, Command::ADD_ERROR_TO_PAGE, "", errors)?;
+            }
+        }
+        if state.image {
+            for url in self.outputs.iter().flatten().filter_map(Output::image_url) {
+                let Some(last) = pages.last_mut() else {
+                    return Err(uninitialized_page(Command::ADD_IMAGE_TO_PAGE));
+                };
+                *last = format!("{last}![]({url})\n");
+            }
+        }
+        if state.trim {
+            trim_added_content(pages, start);
         }
+        state.add_keywords(pages, notebook);
         Ok(())
     }
 }
 
+/// An error of a cell that could not be converted into pages.
+#[derive(Debug)]
+pub struct ConversionError {
+    /// The path of the notebook.
+    pub path: PathBuf,
+    /// The index of the cell in the notebook. The index after the last cell
+    /// stands for the end of the notebook.
+    pub cell: usize,
+    /// The error, which holds the line range inside the cell.
+    pub error: anyhow::Error,
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Cell: {} in File: {:?}. {}",
+            self.cell,
+            self.path,
+            report::render(&self.error, report::use_color())
+        )
+    }
+}
+
+/// A single page of the presentation, with the `name`, `class` and `tags`
+/// properties split from the markdown `body`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Page {
+    /// The id of the page, which is written as `name` property.
+    pub id: Option<String>,
+    /// The class of the page, e.g. `center, middle`.
+    pub class: Option<String>,
+    /// The tags of the page, added by keywords or cell tags.
+    pub tags: Vec<String>,
+    /// Whether the page is only kept for the presenter, which is set with
+    /// [`Command::PresenterOnly`]. It is not written, since the page is
+    /// dropped from the presentation for the audience.
+    pub presenter_only: bool,
+    /// The index of the cell of the notebook that started the page, which is
+    /// not written.
+    pub cell: Option<usize>,
+    /// The markdown content of the page.
+    pub body: String,
+}
+
+impl Page {
+    /// Writes the `pages` one after another, separated by the `separator`.
+    pub fn join(pages: &[Page], separator: &str) -> String {
+        pages
+            .iter()
+            .map(Page::to_string)
+            .collect::<Vec<_>>()
+            .join(&format!("\n{separator}\n\n"))
+    }
+
+    /// Splits the leading `name: ...`, `tags: ...` and `class: ...`
+    /// properties from the markdown of a page.
+    pub fn from_markdown(markdown: &str) -> Page {
+        /// Returns the value of the property `name` at the start of the `text`
+        /// and the remaining text.
+        fn property<'a>(text: &'a str, name: &str) -> Option<(&'a str, &'a str)> {
+            let (value, rest) = text.strip_prefix(name)?.split_once("\n\n")?;
+            (!value.contains('\n')).then_some((value, rest))
+        }
+
+        let mut page = Page::default();
+        let mut body = markdown;
+        if let Some((id, rest)) = property(body, "name: ") {
+            page.id = Some(id.to_string());
+            body = rest;
+        }
+        if let Some((tags, rest)) = property(body, "tags: ") {
+            page.tags = tags.split(", ").map(str::to_string).collect();
+            body = rest;
+        }
+        if let Some((class, rest)) = property(body, "class: ") {
+            page.class = Some(class.to_string());
+            body = rest;
+        }
+        page.body = body.to_string();
+        page
+    }
+}
+
+impl fmt::Display for Page {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(id) = &self.id {
+            write!(f, "name: {id}\n\n")?;
+        }
+        if !self.tags.is_empty() {
+            write!(f, "tags: {}\n\n", self.tags.join(", "))?;
+        }
+        if let Some(class) = &self.class {
+            write!(f, "class: {class}\n\n")?;
+        }
+        write!(f, "{}", self.body)
+    }
+}
+
 /// Representation of a whole `.ipynb` notebook containing the parsed file and
 /// a path to the file.
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Notebook {
     /// All [`Cell`]s in the notebook
     cells: Vec<Cell>,
+    /// The metadata of the notebook.
+    #[serde(default)]
+    metadata: NotebookMetadata,
     #[serde(skip)]
     /// The path to the notebook.
     path: PathBuf,
 }
 
 impl Notebook {
-    /// Converts the whole [`Notebook`] to pages for the presentation.
+    /// Converts the whole [`Notebook`] to pages for the presentation, joined
+    /// by the [`NotebookOptions::separator`]. See [`Notebook::into_page_vec`].
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if either the output or notebook
+    /// path has no parent. Note this case should never happen.
+    pub fn into_pages(self, output_path: &Path, options: &NotebookOptions) -> Result<String> {
+        let (pages, _) = self.into_page_vec(output_path, options)?;
+        Ok(Page::join(&pages, options.separator()))
+    }
+
+    /// Converts the whole [`Notebook`] to a [`Page`] per page of the
+    /// presentation. The cells that are not included by the `options` are
+    /// skipped and the attachments of the cells are embedded as `data:` urls,
+    /// like the local images if [`NotebookOptions::embed_images`] is set.
+    /// The canonical paths of the files included with [`Command::Include`]
+    /// are returned along the pages. The pages of
+    /// [`Command::PresenterOnly`] are dropped unless
+    /// [`NotebookOptions::presenter`] is set, and empty pages without a class
+    /// are dropped if [`NotebookOptions::trim_empty`] is set.
     ///
     /// # Errors
     ///
     /// This function will return an error if either the output or notebook
     /// path has no parent. Note this case should never happen.
-    pub fn into_pages(self, output_path: &Path) -> Result<String> {
+    pub fn into_page_vec(
+        mut self,
+        output_path: &Path,
+        options: &NotebookOptions,
+    ) -> Result<(Vec<Page>, Vec<PathBuf>)> {
+        for cell in &mut self.cells {
+            cell.resolve_attachments();
+        }
+        let (pages, errors, included) = self.proses_cells(options);
+        for err in errors {
+            error!("{}", err);
+        }
+
+        pages
+            .into_iter()
+            .filter(|page| options.keeps(page))
+            .map(|page| {
+                let mut body = page.body;
+                if options.line_numbers {
+                    body = add_line_numbers(&body);
+                }
+                let body = if options.embed_images {
+                    embed_images(&self.path, options.relative_to.as_deref(), body)
+                } else {
+                    Some(body)
+                };
+                let Some(body) = body.and_then(|body| {
+                    replace_paths(
+                        output_path,
+                        &self.path,
+                        options.relative_to.as_deref(),
+                        options.assets.as_deref(),
+                        options.base_url.as_deref(),
+                        body,
+                    )
+                }) else {
+                    return Err(anyhow::Error::msg(format!(
+                        "Either the output path {:?} or the notebook path {:?} has no parent.",
+                        output_path, self.path
+                    )));
+                };
+                Ok(Page { body, ..page })
+            })
+            .collect::<Result<_>>()
+            .map(|pages| (pages, included))
+    }
+
+    /// Returns every error of the commands in the cells of this [`Notebook`]
+    /// that are included by the `options`, instead of logging them one after
+    /// another like [`Notebook::into_pages`]. No pages are written.
+    pub fn validate(&self, options: &NotebookOptions) -> Vec<ConversionError> {
+        self.proses_cells(options).1
+    }
+
+    /// Converts the cells of this [`Notebook`] that are included by the
+    /// `options` into pages with their id, class, tags and the index of the
+    /// cell that started them. Cells that could not be converted are returned
+    /// as [`ConversionError`]s, followed by the files included with
+    /// [`Command::Include`].
+    fn proses_cells(
+        &self,
+        options: &NotebookOptions,
+    ) -> (Vec<Page>, Vec<ConversionError>, Vec<PathBuf>) {
         let mut pages = vec![];
-        let mut page_class = None;
+        let mut cells = vec![];
+        let mut errors = vec![];
+        let mut state = NotebookState {
+            language: self
+                .metadata
+                .kernelspec
+                .as_ref()
+                .and_then(|kernelspec| kernelspec.language.clone()),
+            default_class: self
+                .metadata
+                .presentation
+                .as_ref()
+                .and_then(|presentation| presentation.default_class.clone()),
+            keep_commands: options.keep_commands,
+            strict: options.strict,
+            profile: options.profile.clone(),
+            ..Default::default()
+        };
 
         debug!("Convert notebook {:?} into pages", self.path);
         for (i, cell) in self.cells.iter().enumerate() {
+            if !options.includes(cell.metadata.tags.as_deref().unwrap_or_default()) {
+                debug!("Skip cell {} because of its tags", i);
+                continue;
+            }
             debug!("Convert cell {} into pages", i);
-            if let Err(err) = cell.proses_to_presentation(&mut pages, &mut page_class) {
-                error!("Cell: {} in File: {:?}. {}", i, self.path, err)
+            state.cell = i;
+            if let Err(error) = cell.proses_to_presentation(&self.path, &mut pages, &mut state) {
+                errors.push(ConversionError {
+                    path: self.path.clone(),
+                    cell: i,
+                    error,
+                });
             }
+            cells.resize(pages.len(), i);
         }
-        if let Some(class) = page_class{
-            if let Some(last) = pages.last_mut(){
-                *last = format!("class: {class}\n\n{last}");
-            }else{
-                error!("Cell: {} in File: {:?}. Tried to set a class page that was not initialized. ", self.cells.len(), self.path,)
-            }
+        if let Some(block) = state.profile_block.take() {
+            errors.push(ConversionError {
+                path: self.path.clone(),
+                cell: block.cell,
+                error: anyhow::Error::msg(format!(
+                    "Line {}. The command `{}[{}]` is not closed with `{}`. ",
+                    block.line,
+                    Command::IF_PROFILE,
+                    block.profile,
+                    Command::END_IF
+                )),
+            });
         }
-
-        let pages = pages.join("\n---\n\n");
-        let Some(pages) = replace_paths(output_path, &self.path, pages) else{
-            return Err(anyhow::Error::msg(format!("Either the output path {:?} or the notebook path {:?} has no parent.", output_path,self.path)))
-        };
-        Ok(pages)
+        if let Err(error) = state.apply_page_class(&pages) {
+            errors.push(ConversionError {
+                path: self.path.clone(),
+                cell: self.cells.len(),
+                error,
+            });
+        }
+        let stem = self
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let pages = pages
+            .into_iter()
+            .zip(cells)
+            .enumerate()
+            .map(|(i, (body, cell))| Page {
+                id: state
+                    .ids
+                    .remove(&i)
+                    .or_else(|| options.auto_id.then(|| format!("{stem}-{}", i + 1))),
+                class: state.classes.remove(&i),
+                tags: state.keywords.remove(&i).unwrap_or_default(),
+                presenter_only: state.presenter_only.contains(&i),
+                cell: Some(cell),
+                body,
+            })
+            .collect();
+        (pages, errors, state.included)
     }
 
     /// Try to create a [`Notebook`] from a file in json format.
@@ -316,8 +1788,18 @@ impl Notebook {
     /// This function will return an error if the file could not be read or not parsed from json.
     pub fn try_from_path(path: &PathBuf) -> Result<Notebook> {
         let text = fs::read_to_string(path)?;
-        let mut notebook: Notebook = serde_json::from_str(&text)?;
-        notebook.path = path.clone();
+        Notebook::try_from_json(&text, path.clone())
+    }
+
+    /// Try to create a [`Notebook`] from a `text` in json format. The `path`
+    /// is used to resolve the relative paths of the notebook.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if the text could not be parsed from json.
+    pub fn try_from_json(text: &str, path: PathBuf) -> Result<Notebook> {
+        let mut notebook: Notebook = serde_json::from_str(text)?;
+        notebook.path = path;
 
         Ok(notebook)
     }
@@ -325,31 +1807,55 @@ impl Notebook {
 
 #[cfg(test)]
 mod test {
-    use crate::commands::Command;
+    use std::path::{Path, PathBuf};
+
+    use crate::{commands::Command, path::replace_paths};
+
+    use super::{add_line_numbers, Cell, NotebookOptions, NotebookState, Output, Page};
 
-    use super::Cell;
+    /// Creates a [`Cell`] of the `cell_type` with the lines of the `source`
+    /// and the `tags`, without outputs or attachments.
+    fn cell(cell_type: &str, source: &[&str], tags: &[&str]) -> Cell {
+        Cell {
+            cell_type: cell_type.to_string(),
+            outputs: None,
+            attachments: None,
+            source: source.iter().map(|line| line.to_string()).collect(),
+            metadata: super::Metadata {
+                tags: (!tags.is_empty()).then(|| tags.iter().map(|tag| tag.to_string()).collect()),
+                slideshow: None,
+            },
+        }
+    }
 
     #[test]
     fn test_cell_to_page() {
         let mut pages = vec![];
-        let mut page_class = None;
+        let mut state = NotebookState::default();
         let cell = Cell {
             cell_type: "markdown".to_string(),
             outputs: None,
+            attachments: None,
             source: vec![
-                format!("<!--! {}; {}; -->\n", Command::NEW_PAGE, Command::START_ADD_TO_PAGE),
+                format!(
+                    "<!--! {}; {}; -->\n",
+                    Command::NEW_PAGE,
+                    Command::START_ADD_TO_PAGE
+                ),
                 "# Headline\n".to_string(),
             ],
-            metadata: super::Metadata { tags: None },
+            metadata: super::Metadata::default(),
         };
-        cell.proses_to_presentation(&mut pages, &mut page_class).unwrap();
+        cell.proses_to_presentation(Path::new("notebook.ipynb"), &mut pages, &mut state)
+            .unwrap();
         assert_eq!(pages, vec!["# Headline\n".to_string()]);
 
         let mut pages = vec![];
-        let mut page_class = None;
+        let mut state = NotebookState::default();
         let cell = Cell {
             cell_type: "markdown".to_string(),
             outputs: None,
+            attachments: None,
             source: vec![
                 "<!--!".to_string(),
                 format!("{}\n;", Command::NEW_PAGE),
@@ -359,16 +1865,18 @@ mod test {
                 "Text\n".to_string(),
                 "More Text\n".to_string(),
             ],
-            metadata: super::Metadata { tags: None },
+            metadata: super::Metadata::default(),
         };
-        cell.proses_to_presentation(&mut pages, &mut page_class).unwrap();
+        cell.proses_to_presentation(Path::new("notebook.ipynb"), &mut pages, &mut state)
+            .unwrap();
         assert_eq!(pages, vec!["# Headline\nText\nMore Text\n".to_string()]);
-        
+
         let mut pages = vec![];
-        let mut page_class = None;
+        let mut state = NotebookState::default();
         let cell = Cell {
             cell_type: "markdown".to_string(),
             outputs: None,
+            attachments: None,
             source: vec![
                 format!("<!--!{};\n", Command::NEW_PAGE),
                 format!("{};\n", Command::NEW_PAGE),
@@ -377,10 +1885,1619 @@ mod test {
                 "-->\n".to_string(),
                 "# Headline\n".to_string(),
                 "Text".to_string(),
-                ],
-                metadata: super::Metadata { tags: None },
+            ],
+            metadata: super::Metadata::default(),
+        };
+        cell.proses_to_presentation(Path::new("notebook.ipynb"), &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(
+            pages,
+            vec![
+                "".to_string(),
+                "".to_string(),
+                "".to_string(),
+                "".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tags_to_page() {
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec!["# Headline\n".to_string(), "Text".to_string()],
+            metadata: super::Metadata {
+                tags: Some(vec!["new".to_string(), "start-add".to_string()]),
+                slideshow: None,
+            },
+        };
+        cell.proses_to_presentation(Path::new("notebook.ipynb"), &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(pages, vec!["# Headline\nText\n".to_string()]);
+        assert!(state.keywords.is_empty());
+
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![
+                format!("<!--! {}; -->\n", Command::START_ADD_TO_PAGE),
+                "# Headline\n".to_string(),
+            ],
+            metadata: super::Metadata {
+                tags: Some(vec![
+                    "new-page".to_string(),
+                    format!("{};", Command::NEW_PAGE),
+                    format!("{}[center];", Command::PAGE_CLASS),
+                ]),
+                slideshow: None,
+            },
+        };
+        cell.proses_to_presentation(Path::new("notebook.ipynb"), &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(pages, vec!["# Headline\n".to_string()]);
+        assert_eq!(state.page_class, Some("center".to_string()));
+    }
+
+    #[test]
+    fn test_columns() {
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![
+                format!(
+                    "<!--! {}; {}[]; {}; -->\n",
+                    Command::NEW_PAGE,
+                    Command::COLUMNS,
+                    Command::START_ADD_TO_PAGE
+                ),
+                "Left text\n".to_string(),
+                format!("{}\n", Command::COLUMNS_DELIMITER),
+                "![](./images/image1.png)".to_string(),
+            ],
+            metadata: super::Metadata::default(),
+        };
+        cell.proses_to_presentation(Path::new("notebook.ipynb"), &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(
+            pages,
+            vec![
+                ".left-column[\nLeft text\n]\n.right-column[\n![](./images/image1.png)\n]\n"
+                    .to_string()
+            ]
+        );
+
+        let markdown = replace_paths(
+            Path::new("presentations/output.rmd"),
+            Path::new("notebooks/input.ipynb"),
+            None,
+            None,
+            None,
+            pages.remove(0),
+        );
+        assert_eq!(
+            markdown,
+            Some(".left-column[\nLeft text\n]\n.right-column[\n![](../notebooks/./images/image1.png)\n]\n".to_string())
+        );
+
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![
+                format!("<!--! {}; {}[]; -->\n", Command::NEW_PAGE, Command::COLUMNS),
+                format!("<!--! {}; -->\n", Command::START_ADD_TO_PAGE),
+                "Left text\n".to_string(),
+                "Still left text\n".to_string(),
+            ],
+            metadata: super::Metadata::default(),
+        };
+        let err = cell
+            .proses_to_presentation(Path::new("notebook.ipynb"), &mut pages, &mut state)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Line 0 to 3. Missing column delimiter '|||'"));
+    }
+
+    #[test]
+    fn test_include() {
+        let path = Path::new("tests/notebooks/notebook.ipynb");
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![format!(
+                "<!--! {}; {}[../includes/disclaimer.md]; -->\n",
+                Command::NEW_PAGE,
+                Command::INCLUDE
+            )],
+            metadata: super::Metadata::default(),
+        };
+        cell.proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(
+            pages,
+            vec!["**Disclaimer**\n![](../includes/footer/../../images/image1.png)\n".to_string()]
+        );
+
+        let mut pages = vec![];
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![format!(
+                "<!--! {}; {}[../includes/missing.md]; -->\n",
+                Command::NEW_PAGE,
+                Command::INCLUDE
+            )],
+            metadata: super::Metadata::default(),
+        };
+        let err = cell
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("Line 0 to 0. Unable to include"));
+
+        let mut pages = vec![];
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![format!(
+                "<!--! {}; {}[../includes/recursive_a.md]; -->\n",
+                Command::NEW_PAGE,
+                Command::INCLUDE
+            )],
+            metadata: super::Metadata::default(),
+        };
+        let err = cell
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap_err();
+        assert!(err.to_string().contains("Recursive include"));
+    }
+
+    #[test]
+    fn test_inject_at_page() {
+        let path = Path::new("notebook.ipynb");
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![format!(
+                "<!--! {}; {}; {}[0][Current]; {}[-1][Previous]; -->\n",
+                Command::NEW_PAGE,
+                Command::NEW_PAGE,
+                Command::INJECT_AT_PAGE,
+                Command::INJECT_AT_PAGE
+            )],
+            metadata: super::Metadata::default(),
+        };
+        cell.proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(pages, vec!["Previous".to_string(), "Current".to_string()]);
+
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![format!(
+                "<!--! {}[-2][Content]; -->\n",
+                Command::INJECT_AT_PAGE
+            )],
+            metadata: super::Metadata::default(),
+        };
+        let err = cell
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Tried to insert 'Content' to the page at offset -2, but only 2 pages"));
+        assert_eq!(pages, vec!["Previous".to_string(), "Current".to_string()]);
+    }
+
+    #[test]
+    fn test_add_across_cells() {
+        let path = Path::new("notebook.ipynb");
+        let markdown = |source: Vec<String>| Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source,
+            metadata: super::Metadata::default(),
+        };
+        let mut pages = vec![];
+        let mut notebook = NotebookState::default();
+        let cells = [
+            markdown(vec![
+                format!(
+                    "<!--! {}; {}; -->\n",
+                    Command::NEW_PAGE,
+                    Command::START_ADD_TO_PAGE
+                ),
+                "First cell\n".to_string(),
+            ]),
+            markdown(vec!["Second cell\n".to_string()]),
+            Cell {
+                cell_type: "code".to_string(),
+                outputs: None,
+                attachments: None,
+                source: vec!["x = 1\n".to_string()],
+                metadata: super::Metadata::default(),
+            },
+            markdown(vec![
+                "Third cell\n".to_string(),
+                format!("<!--! {}; -->\n", Command::STOP_ADD_TO_PAGE),
+                "Not added\n".to_string(),
+            ]),
+            markdown(vec!["Not added\n".to_string()]),
+        ];
+        for cell in cells {
+            cell.proses_to_presentation(path, &mut pages, &mut notebook)
+                .unwrap();
+        }
+        assert_eq!(
+            pages,
+            vec!["First cell\nSecond cell\nThird cell\n".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        let text = r##"{
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; set[size = 10]; html[<img src=\"./x.png\" width=\"{{size}}\">] -->"]
+                }
+            ]
+        }"##;
+        let pages =
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap()
+                .into_pages(
+                    Path::new("tests/presentations/output.rmd"),
+                    &NotebookOptions::default(),
+                )
+                .unwrap();
+        assert_eq!(
+            pages,
+            "<img src=\"../../tests/notebooks/./x.png\" width=\"{{size}}\">\n"
+        );
+    }
+
+    #[test]
+    fn test_clear_class() {
+        let path = Path::new("notebook.ipynb");
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![
+                format!(
+                    "<!--! {}; {}[# First]; {}[center]; -->\n",
+                    Command::NEW_PAGE,
+                    Command::INJECT_TP_PAGE,
+                    Command::PAGE_CLASS
+                ),
+                "Text\n".to_string(),
+                format!(
+                    "<!--! {}; {}; {}[# Second]; {}[middle]; {} -->",
+                    Command::CLEAR_CLASS,
+                    Command::NEW_PAGE,
+                    Command::INJECT_TP_PAGE,
+                    Command::PAGE_CLASS,
+                    Command::CLEAR_CLASS
+                ),
+            ],
+            metadata: super::Metadata::default(),
+        };
+        cell.proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        state.apply_page_class(&pages).unwrap();
+        assert_eq!(pages, vec!["# First", "# Second"]);
+    }
+
+    #[test]
+    fn test_trim() {
+        let path = Path::new("notebook.ipynb");
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        let cells = [
+            vec![
+                format!(
+                    "<!--! {}; {} -->\n",
+                    Command::NEW_PAGE,
+                    Command::START_ADD_TO_PAGE
+                ),
+                "\n".to_string(),
+                "# First\n".to_string(),
+                "\n".to_string(),
+            ],
+            vec![
+                format!("<!--! {}; {} -->\n", Command::NEW_PAGE, Command::TRIM),
+                "\n".to_string(),
+                "  \n".to_string(),
+                "# Second\n".to_string(),
+                "\n".to_string(),
+            ],
+            vec![
+                format!("<!--! {} -->\n", Command::TRIM),
+                "\n".to_string(),
+                "Text\n".to_string(),
+                "\n".to_string(),
+            ],
+        ];
+        for source in cells {
+            let cell = Cell {
+                cell_type: "markdown".to_string(),
+                outputs: None,
+                attachments: None,
+                source,
+                metadata: super::Metadata::default(),
             };
-        cell.proses_to_presentation(&mut pages, &mut page_class).unwrap();
-        assert_eq!(pages, vec!["".to_string(),"".to_string(),"".to_string(),"".to_string()]);
+            cell.proses_to_presentation(path, &mut pages, &mut state)
+                .unwrap();
+        }
+        assert_eq!(pages, vec!["\n# First\n\n", "# Second\nText\n"]);
+    }
+
+    #[test]
+    fn test_inject_top() {
+        let path = Path::new("notebook.ipynb");
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![format!("<!--! {}[Top]; -->\n", Command::INJECT_TOP)],
+            metadata: super::Metadata::default(),
+        };
+        let err = cell
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap_err();
+        assert!(err.to_string().contains(
+            "Command `inject-top` used before a page was initialized (add `new;` first)."
+        ));
+
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![
+                format!(
+                    "<!--! {}; {}; -->\n",
+                    Command::NEW_PAGE,
+                    Command::START_ADD_TO_PAGE
+                ),
+                "Text\n".to_string(),
+                format!(
+                    "<!--! {}; {}[# Heading] -->",
+                    Command::STOP_ADD_TO_PAGE,
+                    Command::INJECT_TOP
+                ),
+            ],
+            metadata: super::Metadata::default(),
+        };
+        cell.proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(pages, vec!["# Heading\nText\n".to_string()]);
+
+        // The properties of a page stay in front of the injected content.
+        let mut pages = vec!["class: title\n\nText\n".to_string()];
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![format!("<!--! {}[# Heading] -->", Command::INJECT_TOP)],
+            metadata: super::Metadata::default(),
+        };
+        cell.proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(pages, vec!["class: title\n\n# Heading\nText\n".to_string()]);
+    }
+
+    #[test]
+    fn test_sticky_page_class() {
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![format!(
+                "<!--! {0}[section]; {1}; {2}[A]; {1}; {2}[B]; {1}; {2}[C]; {1}; {0}[]; {2}[D]; {3}[center]; -->\n",
+                Command::STICKY_PAGE_CLASS,
+                Command::NEW_PAGE,
+                Command::INJECT_TP_PAGE,
+                Command::PAGE_CLASS,
+            )],
+            metadata: super::Metadata::default(),
+        };
+        cell.proses_to_presentation(Path::new("notebook.ipynb"), &mut pages, &mut state)
+            .unwrap();
+        state.apply_page_class(&pages).unwrap();
+        assert_eq!(pages, vec!["A", "B", "C", "D"]);
+        assert_eq!(
+            state.classes.into_values().collect::<Vec<_>>(),
+            vec!["section", "section", "section", "center"]
+        );
+    }
+
+    #[test]
+    fn test_keywords() {
+        let cells = vec![
+            Cell {
+                cell_type: "markdown".to_string(),
+                outputs: None,
+                attachments: None,
+                source: vec![
+                    format!("<!--! {}[fire, plume]; -->\n", Command::PAGE_KEYWORDS),
+                    "# Headline\n".to_string(),
+                ],
+                metadata: super::Metadata {
+                    tags: Some(vec![
+                        Command::NEW_PAGE.to_string(),
+                        Command::START_ADD_TO_PAGE.to_string(),
+                        "combustion".to_string(),
+                        "fire".to_string(),
+                    ]),
+                    slideshow: None,
+                },
+            },
+            Cell {
+                cell_type: "markdown".to_string(),
+                outputs: None,
+                attachments: None,
+                source: vec![
+                    format!(
+                        "<!--! {}; {}; -->\n",
+                        Command::NEW_PAGE,
+                        Command::START_ADD_TO_PAGE
+                    ),
+                    "Text".to_string(),
+                ],
+                metadata: super::Metadata::default(),
+            },
+        ];
+
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        cells[0]
+            .proses_to_presentation(Path::new("notebook.ipynb"), &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(
+            state.keywords.get(&0),
+            Some(&vec![
+                "fire".to_string(),
+                "plume".to_string(),
+                "combustion".to_string()
+            ])
+        );
+
+        let notebook = super::Notebook {
+            cells,
+            metadata: Default::default(),
+            path: PathBuf::from("tests/notebooks/notebook.ipynb"),
+        };
+        let pages = notebook
+            .into_pages(
+                Path::new("tests/presentations/output.rmd"),
+                &NotebookOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            pages,
+            "tags: fire, plume, combustion\n\n# Headline\n\n---\n\nText\n"
+        );
+    }
+
+    #[test]
+    fn test_tag_filter() {
+        let cell = |text: &str, tags: Option<Vec<String>>| Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![
+                format!(
+                    "<!--! {}; {}; -->\n",
+                    Command::NEW_PAGE,
+                    Command::START_ADD_TO_PAGE
+                ),
+                text.to_string(),
+            ],
+            metadata: super::Metadata {
+                tags,
+                slideshow: None,
+            },
+        };
+        let into_pages = |options: NotebookOptions| {
+            super::Notebook {
+                cells: vec![
+                    cell("General", None),
+                    cell(
+                        "Instructor",
+                        Some(vec![
+                            Command::NEW_PAGE.to_string(),
+                            "instructor".to_string(),
+                        ]),
+                    ),
+                    cell("Student", Some(vec!["student".to_string()])),
+                ],
+                metadata: Default::default(),
+                path: PathBuf::from("tests/notebooks/notebook.ipynb"),
+            }
+            .into_pages(Path::new("tests/presentations/output.rmd"), &options)
+            .unwrap()
+        };
+
+        let pages = into_pages(NotebookOptions {
+            only_tags: vec!["instructor".to_string()],
+            ..Default::default()
+        });
+        assert!(pages.contains("General"));
+        assert!(pages.contains("Instructor"));
+        assert!(!pages.contains("Student"));
+
+        let pages = into_pages(NotebookOptions {
+            skip_tags: vec!["instructor".to_string()],
+            ..Default::default()
+        });
+        assert!(pages.contains("General"));
+        assert!(!pages.contains("Instructor"));
+        assert!(pages.contains("Student"));
+    }
+
+    #[test]
+    fn test_line_numbers() {
+        let markdown =
+            "# Code\n```python\nprint(1)\n```\n\n  ```\nplain\n```\n```rust {.numbers}\n```\n";
+        assert_eq!(
+            add_line_numbers(markdown),
+            "# Code\n```python {.line-numbers}\nprint(1)\n```\n\n  ``` {.line-numbers}\nplain\n```\n```rust {.numbers}\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_variables() {
+        let path = Path::new("notebook.ipynb");
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        let cells = [
+            Cell {
+                cell_type: "markdown".to_string(),
+                outputs: None,
+                attachments: None,
+                source: vec![format!(
+                    "<!--! {}[course = Fire Dynamics]; {}; -->\n",
+                    Command::SET_VARIABLE,
+                    Command::NEW_PAGE
+                )],
+                metadata: super::Metadata::default(),
+            },
+            Cell {
+                cell_type: "markdown".to_string(),
+                outputs: None,
+                attachments: None,
+                source: vec![
+                    format!("<!--! {}; -->\n", Command::START_ADD_TO_PAGE),
+                    "# {{course}} {{ course }}\n".to_string(),
+                    "{{semester}}".to_string(),
+                ],
+                metadata: super::Metadata::default(),
+            },
+        ];
+        for cell in cells {
+            cell.proses_to_presentation(path, &mut pages, &mut state)
+                .unwrap();
+        }
+        assert_eq!(
+            pages,
+            vec!["# Fire Dynamics Fire Dynamics\n{{semester}}\n".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_fragment() {
+        let path = Path::new("notebook.ipynb");
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![
+                format!(
+                    "<!--! {}; {}; {}; -->\n",
+                    Command::NEW_PAGE,
+                    Command::FRAGMENT,
+                    Command::START_ADD_TO_PAGE
+                ),
+                "# Headline\n".to_string(),
+                "\n".to_string(),
+                "- First\n".to_string(),
+                "  still first\n".to_string(),
+                "- Second\n".to_string(),
+                "- Third".to_string(),
+            ],
+            metadata: super::Metadata::default(),
+        };
+        cell.proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(
+            pages,
+            vec![
+                "# Headline\n\n--\n- First\n  still first\n--\n- Second\n--\n- Third\n".to_string()
+            ]
+        );
+
+        let mut pages = vec![];
+        let cell = Cell {
+            cell_type: "markdown".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![
+                format!(
+                    "<!--! {}; {}; {}; -->\n",
+                    Command::NEW_PAGE,
+                    Command::FRAGMENT,
+                    Command::START_ADD_TO_PAGE
+                ),
+                "- First\n".to_string(),
+                "- Second\n".to_string(),
+                "![](./image.png)\n".to_string(),
+                "<!--!\n".to_string(),
+                format!("{}[<img src=\"{{}}\">];\n", Command::WRAP_IMAGE),
+                "-->\n".to_string(),
+                "\n".to_string(),
+            ],
+            metadata: super::Metadata::default(),
+        };
+        cell.proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(
+            pages,
+            vec!["- First\n--\n- Second\n![](./image.png)\n<img src=\"./image.png\">".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_code() {
+        let path = Path::new("notebook.ipynb");
+        let mut pages = vec![String::new()];
+        let mut state = NotebookState::default();
+        let cell = Cell {
+            cell_type: "code".to_string(),
+            outputs: None,
+            attachments: None,
+            source: vec![
+                format!("# <!--! {} -->\n", Command::CODE),
+                "x = 1\n".to_string(),
+                "print(x)".to_string(),
+            ],
+            metadata: super::Metadata::default(),
+        };
+        cell.proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(pages, vec!["```python\nx = 1\nprint(x)\n```\n".to_string()]);
+
+        let text = r#"{
+            "cells": [
+                {"cell_type": "markdown", "metadata": {}, "source": ["<!--! new -->"]},
+                {"cell_type": "code", "metadata": {"tags": ["code"]}, "outputs": [], "source": ["x <- 1"]},
+                {"cell_type": "code", "metadata": {}, "outputs": [], "source": ["hidden <- 2"]}
+            ],
+            "metadata": {"kernelspec": {"language": "R", "name": "ir"}}
+        }"#;
+        let notebook =
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap();
+        let pages = notebook
+            .into_pages(
+                Path::new("tests/presentations/output.rmd"),
+                &NotebookOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(pages, "```R\nx <- 1\n```\n");
+    }
+
+    #[test]
+    fn test_stream_output() {
+        let path = Path::new("notebook.ipynb");
+        let mut stream = cell(
+            "code",
+            &["print('Fire')\n", "print('Plume')"],
+            &[Command::ADD_STREAM_TO_PAGE],
+        );
+        stream.outputs = Some(vec![
+            Output::Stream {
+                text: vec!["Fire\n".to_string()],
+            },
+            Output::Other {},
+            Output::Stream {
+                text: vec!["Plume".to_string()],
+            },
+        ]);
+
+        let mut pages = vec![String::new()];
+        let mut state = NotebookState::default();
+        stream
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(pages, vec!["```\nFire\nPlume\n```\n".to_string()]);
+
+        let mut pages = vec![String::new()];
+        stream.outputs = None;
+        stream
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(pages, vec![String::new()]);
+    }
+
+    #[test]
+    fn test_error_output() {
+        let path = Path::new("notebook.ipynb");
+        let source = format!("# <!--! {} -->\n", Command::ADD_ERROR_TO_PAGE);
+        let mut error = cell("code", &[&source], &[]);
+        error.outputs = Some(vec![
+            Output::Error {
+                ename: "ZeroDivisionError".to_string(),
+                evalue: "division by zero".to_string(),
+            },
+            Output::Stream {
+                text: vec!["Ignored".to_string()],
+            },
+            Output::Error {
+                ename: "NameError".to_string(),
+                evalue: "name 'x' is not defined".to_string(),
+            },
+        ]);
+
+        let mut pages = vec![String::new()];
+        let mut state = NotebookState::default();
+        error
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(
+            pages,
+            vec![
+                "```\nZeroDivisionError: division by zero\nNameError: name 'x' is not defined\n```\n"
+                    .to_string()
+            ]
+        );
+
+        let mut pages = vec![String::new()];
+        error.outputs = Some(vec![]);
+        error
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(pages, vec![String::new()]);
+    }
+
+    #[test]
+    fn test_image_output() {
+        let path = Path::new("notebook.ipynb");
+        let source = format!("# <!--! {} -->\n", Command::ADD_IMAGE_TO_PAGE);
+        let mut image = cell("code", &[&source], &[]);
+        let data = |entries: &[(&str, serde_json::Value)]| Output::DisplayData {
+            data: entries
+                .iter()
+                .map(|(mime, value)| (mime.to_string(), value.clone()))
+                .collect(),
+        };
+
+        let mut pages = vec![String::new()];
+        let mut state = NotebookState::default();
+        image.outputs = Some(vec![
+            data(&[
+                ("text/plain", serde_json::json!("<Figure>")),
+                ("image/jpeg", serde_json::json!("/9j/4AAQ")),
+                ("image/png", serde_json::json!(["iVBORw0K\n", "GgoAAAAN"])),
+            ]),
+            data(&[("text/plain", serde_json::json!("42"))]),
+        ]);
+        image
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(
+            pages,
+            vec!["![](data:image/png;base64,iVBORw0KGgoAAAAN)\n".to_string()]
+        );
+
+        let mut pages = vec![String::new()];
+        image.outputs = Some(vec![Output::Stream {
+            text: vec!["No image".to_string()],
+        }]);
+        image
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(pages, vec![String::new()]);
+    }
+
+    #[test]
+    fn test_slide_type() {
+        let path = Path::new("notebook.ipynb");
+        let slide = |cell_type: &str, slide_type: &str, text: &str| {
+            let mut cell = cell(cell_type, &[text], &[]);
+            cell.metadata.slideshow = Some(super::Slideshow {
+                slide_type: Some(slide_type.to_string()),
+            });
+            cell
+        };
+
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        let cells = [
+            slide("markdown", "-", "Ignored\n"),
+            slide("markdown", "slide", "# Slide\n"),
+            slide("markdown", "-", "Continued\n"),
+            slide("markdown", "fragment", "Fragment\n"),
+            slide("code", "-", "x = 1"),
+            slide("markdown", "skip", "Skipped\n"),
+            slide("markdown", "notes", "Note\n"),
+            slide("markdown", "notes", "Another note\n"),
+            slide("markdown", "subslide", "## Subslide\n"),
+        ];
+        for cell in cells {
+            cell.proses_to_presentation(path, &mut pages, &mut state)
+                .unwrap();
+        }
+        assert_eq!(
+            pages,
+            vec![
+                "# Slide\nContinued\n--\nFragment\n```python\nx = 1\n```\n???\nNote\nAnother note\n"
+                    .to_string(),
+                "## Subslide\n".to_string(),
+            ]
+        );
+
+        let mut pages = vec![];
+        assert!(slide("markdown", "unknown", "Text")
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .is_err());
+    }
+
+    #[test]
+    fn test_raw() {
+        let path = Path::new("notebook.ipynb");
+        let source = [
+            "<!--! new -->\n",
+            "<div class=\"box\">\n",
+            "  <img src=\"./image.png\">\n",
+            "</div>",
+        ];
+
+        let mut pages = vec![String::new()];
+        let mut state = NotebookState::default();
+        cell("raw", &source, &[Command::RAW])
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(
+            pages,
+            vec![
+                "<!--! new -->\n<div class=\"box\">\n  <img src=\"./image.png\">\n</div>\n"
+                    .to_string()
+            ]
+        );
+
+        let mut pages = vec![String::new()];
+        cell("raw", &source, &[])
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap();
+        assert_eq!(pages, vec![String::new()]);
+
+        let mut pages = vec![];
+        assert!(cell("raw", &source, &[Command::START_ADD_TO_PAGE])
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .is_err());
+    }
+
+    #[test]
+    fn test_source_without_commands_comment() {
+        let source = cell(
+            "markdown",
+            &[
+                "<!--! new -->\n",
+                "Before <!--! class[center] --> after\n",
+                "![](a.png)<!--! image[{}] -->\n",
+                "<!--! image[{}] -->![](b.png)\n",
+                "<!--! first --> middle <!--! second -->\n",
+                "Start <!--! inject[\n",
+                "Text\n",
+                "] --> end",
+            ],
+            &[],
+        )
+        .get_source_without_commands_comment()
+        .unwrap();
+        assert_eq!(
+            source,
+            "Before  after\n![](a.png)\n![](b.png)\n middle \nStart \n end"
+        );
+
+        let source = cell(
+            "code",
+            &["# <!--! code -->\n", "x = 1 # <!--! output -->\n"],
+            &[],
+        )
+        .get_source_without_commands_comment()
+        .unwrap();
+        assert_eq!(source, "x = 1 # \n");
+    }
+
+    #[test]
+    fn test_auto_split() {
+        let path = Path::new("notebook.ipynb");
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        cell(
+            "markdown",
+            &[
+                "<!--! new; start-add; autosplit -->\n",
+                "## First\n",
+                "Text\n",
+                "```python\n",
+                "## Not a heading\n",
+                "```\n",
+                "## Second\n",
+                "More text",
+            ],
+            &[],
+        )
+        .proses_to_presentation(path, &mut pages, &mut state)
+        .unwrap();
+        assert_eq!(
+            pages,
+            vec![
+                "## First\nText\n```python\n## Not a heading\n```\n".to_string(),
+                "## Second\nMore text\n".to_string(),
+            ]
+        );
+
+        let mut pages = vec![];
+        cell(
+            "markdown",
+            &[
+                "<!--! new; start-add; autosplit -->\n",
+                "Intro\n",
+                "## First\n",
+            ],
+            &[],
+        )
+        .proses_to_presentation(path, &mut pages, &mut state)
+        .unwrap();
+        assert_eq!(pages, vec!["Intro\n".to_string(), "## First\n".to_string()]);
+    }
+
+    #[test]
+    fn test_unclosed_comment() {
+        let path = Path::new("notebook.ipynb");
+        let mut pages = vec![];
+        let mut state = NotebookState::default();
+        let err = cell(
+            "markdown",
+            &[
+                "<!--! new -->\n",
+                "Text\n",
+                "<!--! start-add;\n",
+                "inject[Text]\n",
+            ],
+            &[],
+        )
+        .proses_to_presentation(path, &mut pages, &mut state)
+        .unwrap_err();
+        assert!(err.to_string().starts_with(
+            "Line 2 to 3. Missing comment closing element of the comment opened at line 2."
+        ));
+
+        let err = cell("markdown", &["Text\n", "<!--!"], &[])
+            .proses_to_presentation(path, &mut pages, &mut state)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Missing comment closing element of the comment opened at line 1."));
+    }
+
+    #[test]
+    fn test_attachments() {
+        let text = r#"{
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "attachments": {
+                        "image.png": {"image/png": "iVBORw0KGgo="}
+                    },
+                    "source": [
+                        "<!--! new; start-add -->\n",
+                        "![](attachment:image.png)\n",
+                        "![](attachment:missing.png)"
+                    ]
+                }
+            ]
+        }"#;
+        let notebook =
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap();
+        let pages = notebook
+            .into_pages(
+                Path::new("tests/presentations/output.rmd"),
+                &NotebookOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(
+            pages,
+            "![](data:image/png;base64,iVBORw0KGgo=)\n![](attachment:missing.png)\n"
+        );
+    }
+
+    #[test]
+    fn test_page_vec() {
+        let text = r#"{
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "metadata": {"tags": ["intro"]},
+                    "source": ["<!--! new; class[center]; id[first]; inject[# First] -->"]
+                },
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; inject[# Second] -->"]
+                },
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; inject[# Third]; class[middle] -->"]
+                }
+            ]
+        }"#;
+        let notebook =
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap();
+        let (pages, _) = notebook
+            .into_page_vec(
+                Path::new("tests/presentations/output.rmd"),
+                &NotebookOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(pages.len(), 3);
+        assert_eq!(pages[0].class.as_deref(), Some("center"));
+        assert_eq!(pages[0].tags, vec!["intro".to_string()]);
+        assert_eq!(pages[0].body, "# First");
+        assert_eq!(pages[0].id.as_deref(), Some("first"));
+        assert_eq!(pages[1].class, None);
+        assert_eq!(pages[1].id, None);
+        assert_eq!(pages[2].class.as_deref(), Some("middle"));
+        assert_eq!(
+            pages.iter().map(Page::to_string).collect::<Vec<_>>(),
+            vec![
+                "name: first\n\ntags: intro\n\nclass: center\n\n# First",
+                "# Second",
+                "class: middle\n\n# Third"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_uninitialized_page() {
+        for (source, name) in [
+            ("<!--! inject[Text] -->", Command::INJECT_TP_PAGE),
+            (
+                "<!--! image[{}] -->\n![Plot](plot.png)",
+                Command::WRAP_IMAGE,
+            ),
+            ("<!--! start-add -->\nText", Command::START_ADD_TO_PAGE),
+            ("<!--! class[center] -->", Command::PAGE_CLASS),
+        ] {
+            let text = format!(
+                r#"{{"cells": [{{"cell_type": "markdown", "metadata": {{}}, "source": {}}}]}}"#,
+                serde_json::to_string(&[source]).unwrap()
+            );
+            let notebook = super::Notebook::try_from_json(
+                &text,
+                PathBuf::from("tests/notebooks/notebook.ipynb"),
+            )
+            .unwrap();
+            let errors = notebook.validate(&NotebookOptions::default());
+            assert_eq!(errors.len(), 1, "{source}");
+            assert!(
+                errors[0].error.to_string().contains(&format!(
+                    "Command `{name}` used before a page was initialized (add `new;` first)."
+                )),
+                "{}",
+                errors[0].error
+            );
+        }
+    }
+
+    #[test]
+    fn test_keep_commands() {
+        let text = r#"{
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; start-add -->\n", "Text\n"]
+                },
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; -->"]
+                }
+            ]
+        }"#;
+        let notebook =
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap();
+        let (pages, _, _) = notebook.proses_cells(&NotebookOptions {
+            keep_commands: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            pages.iter().map(Page::to_string).collect::<Vec<_>>(),
+            vec!["<!-- new; start-add -->\nText\n", "<!-- new; -->\n"]
+        );
+
+        let (pages, _, _) = notebook.proses_cells(&NotebookOptions::default());
+        assert_eq!(
+            pages.iter().map(Page::to_string).collect::<Vec<_>>(),
+            vec!["Text\n", ""]
+        );
+    }
+
+    #[test]
+    fn test_auto_id() {
+        let text = r#"{
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! id[intro] -->"]
+                },
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; inject[# First] -->"]
+                },
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; id[intro]; inject[# Second]; new; inject[# Third] -->"]
+                }
+            ]
+        }"#;
+        let notebook =
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap();
+        let (pages, errors, _) = notebook.proses_cells(&NotebookOptions {
+            auto_id: true,
+            ..Default::default()
+        });
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0]
+            .error
+            .to_string()
+            .contains("Command `id` used before a page was initialized (add `new;` first)."));
+        assert_eq!(
+            pages.iter().map(Page::to_string).collect::<Vec<_>>(),
+            vec![
+                "name: notebook-1\n\n# First",
+                "name: intro\n\n# Second",
+                "name: notebook-3\n\n# Third"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_crlf_line_endings() {
+        let notebook = |source: &str| {
+            let text = format!(
+                r#"{{"cells": [{{"cell_type": "markdown", "metadata": {{}}, "source": {source}}}]}}"#
+            );
+            super::Notebook::try_from_json(&text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap()
+                .into_pages(
+                    Path::new("tests/presentations/output.rmd"),
+                    &NotebookOptions::default(),
+                )
+                .unwrap()
+        };
+
+        let lf =
+            notebook(r##"["<!--!\n", "new;\n", "start-add\n", "-->\n", "# Title\n", "Text"]"##);
+        let crlf = notebook(
+            r##"["<!--!\r\n", "new;\r\n", "start-add\r\n", "-->\r\n", "# Title\r\n", "Text"]"##,
+        );
+        let cr = notebook(r##"["<!--!\rnew;\rstart-add\r-->\r# Title\rText"]"##);
+        assert_eq!(lf, "# Title\nText\n");
+        assert_eq!(crlf, lf);
+        assert_eq!(cr, lf);
+    }
+
+    #[test]
+    fn test_source_as_string() {
+        let lines: super::Cell = serde_json::from_str(
+            r##"{"cell_type": "markdown", "metadata": {}, "source": ["<!--! new; start-add -->\n", "# Title\n", "Text"]}"##,
+        )
+        .unwrap();
+        let text: super::Cell = serde_json::from_str(
+            r##"{"cell_type": "markdown", "metadata": {}, "source": "<!--! new; start-add -->\n# Title\nText"}"##,
+        )
+        .unwrap();
+        assert_eq!(
+            text.source,
+            vec!["<!--! new; start-add -->\n", "# Title\n", "Text"]
+        );
+        assert_eq!(text.source, lines.source);
+        assert_eq!(text.cell_type, lines.cell_type);
+
+        let empty: super::Cell =
+            serde_json::from_str(r#"{"cell_type": "markdown", "metadata": {}, "source": ""}"#)
+                .unwrap();
+        assert!(empty.source.is_empty());
+    }
+
+    #[test]
+    fn test_if_profile() {
+        let text = r##"{
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; start-add -->\n", "# Task\n"]
+                },
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": [
+                        "<!--! if-profile[instructor] -->\n",
+                        "Solution\n",
+                        "<!--! new; inject[# Notes]; -->\n",
+                        "<!--! end-if; -->\n",
+                        "<!--! if-profile[student] -->\n",
+                        "Try it yourself\n",
+                        "<!--! end-if -->\n"
+                    ]
+                }
+            ]
+        }"##;
+        let notebook =
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap();
+        let proses = |profile: Option<&str>| {
+            let (pages, errors, _) = notebook.proses_cells(&NotebookOptions {
+                profile: profile.map(str::to_string),
+                ..Default::default()
+            });
+            assert!(errors.is_empty());
+            pages.iter().map(Page::to_string).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            proses(Some("instructor")),
+            vec!["# Task\nSolution\n", "# Notes"]
+        );
+        assert_eq!(proses(Some("student")), vec!["# Task\nTry it yourself\n"]);
+        assert_eq!(proses(None), vec!["# Task\n"]);
+
+        let text = r##"{
+            "cells": [
+                {"cell_type": "markdown", "metadata": {}, "source": ["<!--! new -->"]},
+                {"cell_type": "markdown", "metadata": {}, "source": ["\n", "<!--! if-profile[student] -->"]},
+                {"cell_type": "markdown", "metadata": {}, "source": ["<!--! end-if; end-if -->"]},
+                {"cell_type": "markdown", "metadata": {}, "source": ["<!--! if-profile[student] -->"]}
+            ]
+        }"##;
+        let notebook =
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap();
+        let errors = notebook.validate(&NotebookOptions::default());
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].cell, 2);
+        assert!(errors[0].error.to_string().contains("has no matching"));
+        assert_eq!(errors[1].cell, 3);
+        assert!(errors[1]
+            .error
+            .to_string()
+            .starts_with("Line 0. The command `if-profile[student]` is not closed"));
+    }
+
+    #[test]
+    fn test_presenter_only() {
+        let text = r##"{
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; inject[# First] -->"]
+                },
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; class[notes]; presenter-only; inject[# Notes] -->"]
+                },
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; inject[# Last]; presenter-only -->"]
+                }
+            ]
+        }"##;
+        let into_pages = |presenter: bool| {
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap()
+                .into_pages(
+                    Path::new("tests/presentations/output.rmd"),
+                    &NotebookOptions {
+                        presenter,
+                        ..Default::default()
+                    },
+                )
+                .unwrap()
+        };
+
+        assert_eq!(into_pages(false), "# First");
+        assert_eq!(
+            into_pages(true),
+            "# First\n---\n\nclass: notes\n\n# Notes\n---\n\n# Last"
+        );
+    }
+
+    #[test]
+    fn test_page_properties_in_content() {
+        let text = r##"{
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; start-add -->\n", "presenter: only\n", "\n", "# First"]
+                },
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new -->\n", "name: second\n", "\n", "# Second"]
+                }
+            ]
+        }"##;
+        let (pages, _) =
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap()
+                .into_page_vec(
+                    Path::new("tests/presentations/output.rmd"),
+                    &NotebookOptions::default(),
+                )
+                .unwrap();
+
+        assert_eq!(pages.len(), 2);
+        assert!(!pages[0].presenter_only);
+        assert_eq!(pages[0].body, "presenter: only\n\n# First\n");
+        assert_eq!(pages[1].id, None);
+        assert_eq!(pages[1].body, "name: second\n\n# Second\n");
+    }
+
+    #[test]
+    fn test_default_class() {
+        let text = r##"{
+            "metadata": {"presentation": {"default_class": "middle"}},
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; inject[# First] -->"]
+                },
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; class[center]; inject[# Second] -->"]
+                },
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; inject[# Last] -->"]
+                }
+            ]
+        }"##;
+        let pages =
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap()
+                .into_pages(
+                    Path::new("tests/presentations/output.rmd"),
+                    &NotebookOptions::default(),
+                )
+                .unwrap();
+        assert_eq!(
+            pages,
+            "class: middle\n\n# First\n---\n\nclass: center\n\n# Second\n---\n\nclass: middle\n\n# Last"
+        );
+    }
+
+    #[test]
+    fn test_trim_empty() {
+        let into_pages = |source: &str, trim_empty: bool| {
+            let text = format!(
+                r#"{{"cells": [{{"cell_type": "markdown", "metadata": {{}}, "source": ["{source}"]}}]}}"#
+            );
+            super::Notebook::try_from_json(&text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap()
+                .into_page_vec(
+                    Path::new("tests/presentations/output.rmd"),
+                    &NotebookOptions {
+                        trim_empty,
+                        ..Default::default()
+                    },
+                )
+                .unwrap()
+                .0
+        };
+
+        assert_eq!(into_pages("<!--! new; new; new; new -->", false).len(), 4);
+        assert_eq!(into_pages("<!--! new; new; new; new -->", true), vec![]);
+        assert_eq!(
+            into_pages(
+                "<!--! new; new; class[center]; new; new; inject[# Last] -->",
+                true
+            )
+            .iter()
+            .map(Page::to_string)
+            .collect::<Vec<_>>(),
+            vec!["class: center\n\n", "# Last"]
+        );
+    }
+
+    #[test]
+    fn test_source_without_newlines() {
+        let text = r##"{
+            "cells": [
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! new; start-add -->", "# Title", "Text"]
+                },
+                {
+                    "cell_type": "markdown",
+                    "metadata": {},
+                    "source": ["<!--! stop-add -->", "First <!--! new --> line", "Second line"]
+                },
+                {
+                    "cell_type": "code",
+                    "metadata": {},
+                    "outputs": [],
+                    "source": ["# <!--! code -->", "x = 1", "y = 2"]
+                }
+            ]
+        }"##;
+        let notebook =
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap();
+        assert_eq!(
+            notebook.cells[0]
+                .get_source_without_commands_comment()
+                .unwrap(),
+            "# Title\nText"
+        );
+        assert_eq!(
+            notebook.cells[1]
+                .get_source_without_commands_comment()
+                .unwrap(),
+            "First  line\nSecond line"
+        );
+
+        let (pages, errors, _) = notebook.proses_cells(&NotebookOptions::default());
+        assert!(errors.is_empty());
+        assert_eq!(
+            pages[0].to_string(),
+            "# Title\nText\n```python\nx = 1\ny = 2\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_without_cell_metadata() {
+        let text = r#"{
+            "nbformat": 4,
+            "nbformat_minor": 5,
+            "generator": "converter",
+            "cells": [
+                {"cell_type": "markdown", "id": "a1", "source": ["<!--! new; inject[# Title] -->"]}
+            ]
+        }"#;
+        let notebook =
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap();
+        assert!(notebook.cells[0].metadata.tags.is_none());
+        let pages = notebook
+            .into_pages(
+                Path::new("tests/presentations/output.rmd"),
+                &NotebookOptions::default(),
+            )
+            .unwrap();
+        assert_eq!(pages, "# Title");
+    }
+
+    #[test]
+    fn test_validate() {
+        let text = r#"{
+            "cells": [
+                {"cell_type": "markdown", "metadata": {}, "source": ["<!--! nwe -->"]},
+                {"cell_type": "markdown", "metadata": {}, "source": ["<!--! new; start-add -->\n", "Text\n"]},
+                {"cell_type": "markdown", "metadata": {}, "source": ["<!--! class[center]\n", "Text\n"]}
+            ]
+        }"#;
+        let notebook =
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap();
+        let errors = notebook.validate(&NotebookOptions::default());
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].cell, 0);
+        assert!(errors[0]
+            .error
+            .to_string()
+            .contains("Unknown command 'nwe'"));
+        assert_eq!(errors[1].cell, 2);
+        assert!(errors[1]
+            .error
+            .to_string()
+            .contains("Missing comment closing element"));
+        assert!(errors[1]
+            .to_string()
+            .starts_with("Cell: 2 in File: \"tests/notebooks/notebook.ipynb\". Line 0 to 1."));
+
+        let text = r#"{
+            "cells": [
+                {"cell_type": "markdown", "metadata": {"tags": ["draft"]}, "source": ["<!--! nwe -->"]},
+                {"cell_type": "markdown", "metadata": {}, "source": ["<!--! new; -->\n", "Text\n"]}
+            ]
+        }"#;
+        let notebook =
+            super::Notebook::try_from_json(text, PathBuf::from("tests/notebooks/notebook.ipynb"))
+                .unwrap();
+        assert_eq!(notebook.validate(&NotebookOptions::default()).len(), 1);
+        let options = NotebookOptions {
+            skip_tags: vec!["draft".to_string()],
+            ..Default::default()
+        };
+        assert!(notebook.validate(&options).is_empty());
+    }
+
+    #[test]
+    fn test_try_from_json() {
+        let path = PathBuf::from("tests/notebooks/class.ipynb");
+        let output_path = Path::new("tests/presentations/output.rmd");
+        let text = std::fs::read_to_string(&path).unwrap();
+        let from_json = super::Notebook::try_from_json(&text, path.clone())
+            .unwrap()
+            .into_pages(output_path, &NotebookOptions::default())
+            .unwrap();
+        let from_path = super::Notebook::try_from_path(&path)
+            .unwrap()
+            .into_pages(output_path, &NotebookOptions::default())
+            .unwrap();
+        assert_eq!(from_json, from_path);
+
+        let text = r#"{"cells": [{"cell_type": "markdown", "metadata": {}, "source": ["<!--! new; inject[Text] -->"]}]}"#;
+        let pages = super::Notebook::try_from_json(text, path)
+            .unwrap()
+            .into_pages(output_path, &NotebookOptions::default())
+            .unwrap();
+        assert_eq!(pages, "Text");
     }
 }
@@ -3,14 +3,17 @@
 //! destination of the presentation file. This crate provides functionality for parsing
 //! and manipulating the image paths.
 use anyhow::Result;
+use base64::Engine;
 use chumsky::{prelude::*, text::whitespace};
+use log::warn;
 use std::{
     error::Error,
     ffi::OsStr,
     fmt::Display,
+    fs, io,
     num::ParseIntError,
     ops::Range,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
 };
 
 /// Creates a parser wich returns the span of the content of a dubble qouted string.
@@ -27,21 +30,60 @@ fn single_quote_string() -> impl Parser<char, (String, Range<usize>), Error = Si
         .padded_by(just('\''))
 }
 
-/// Searches for a HTML element in a markdown stream and returns all possible src path spans.
-fn find_paths_in_html() -> impl Parser<char, (String, Range<usize>), Error = Simple<char>> {
-    let src = just::<_, _, Simple<char>>("src")
-        .then(whitespace())
-        .then(just('='))
-        .then(whitespace());
+/// Searches for a HTML element in a markdown stream and returns the spans of the paths of all its `src`, `srcset` and
+/// `poster` attributes, e.g. of an `<img>`, `<video>` or `<source>` element. The children of a `<picture>` or `<video>`
+/// are elements of their own, so every `<source>` and the fallback `<img>` is found.
+fn find_paths_in_html() -> impl Parser<char, Vec<(String, Range<usize>)>, Error = Simple<char>> {
+    let attribute = one_of(" \t\r\n").ignore_then(html_path_attribute());
+    let other = none_of("<>").to(vec![]);
 
-    let inner = take_until(src.ignored())
-        .ignored()
-        .then(duble_quote_string().or(single_quote_string()))
-        .map(|(_, s)| s)
-        .then(take_until(just('>').ignored().rewind()))
-        .map(|(s, _)| s);
+    html_path_attribute()
+        .or_not()
+        .then(attribute.or(other).repeated())
+        .delimited_by(just('<').ignored(), just('>').ignored())
+        .map(|(first, paths)| first.into_iter().chain(paths).flatten().collect())
+}
+
+/// Creates a parser wich returns the spans of the paths of a `srcset`, `src` or `poster` attribute of a HTML element.
+/// Every candidate of a `srcset` has its own span, which excludes the descriptor like `2x`.
+fn html_path_attribute() -> impl Parser<char, Vec<(String, Range<usize>)>, Error = Simple<char>> {
+    let value = || {
+        whitespace()
+            .then(just('='))
+            .then(whitespace())
+            .ignore_then(duble_quote_string().or(single_quote_string()))
+    };
+
+    let srcset = just::<_, _, Simple<char>>("srcset")
+        .ignore_then(value())
+        .map(|(value, span)| split_srcset(&value, span.start));
+    let src = just("src")
+        .or(just("poster"))
+        .ignore_then(value())
+        .map(|s| vec![s]);
+
+    srcset.or(src)
+}
+
+/// Splits the `value` of a `srcset` attribute, that starts at the char offset `start`, into the paths of its candidates
+/// with their spans.
+fn split_srcset(value: &str, start: usize) -> Vec<(String, Range<usize>)> {
+    let mut candidates = vec![];
+    let mut offset = start;
+    for candidate in value.split(',') {
+        let leading = candidate.chars().take_while(|c| c.is_whitespace()).count();
+        let path = candidate.split_whitespace().next().unwrap_or_default();
+        if !path.is_empty() {
+            let path_start = offset + leading;
+            candidates.push((
+                path.to_string(),
+                path_start..path_start + path.chars().count(),
+            ));
+        }
+        offset += candidate.chars().count() + 1;
+    }
 
-    inner.delimited_by(just('<').ignored(), just('>').ignored())
+    candidates
 }
 
 /// Searches for a markdown image element in a markdown stream and returns the path span.
@@ -58,12 +100,42 @@ fn find_path_in_markdown_image() -> impl Parser<char, (String, Range<usize>), Er
     start.then(end).map(|(_, s)| s)
 }
 
+/// Searches for a markdown link element in a markdown stream and returns the span of its target.
+fn find_link_in_markdown() -> impl Parser<char, (String, Range<usize>), Error = Simple<char>> {
+    let start = none_of::<_, _, Simple<char>>("[]")
+        .repeated()
+        .ignored()
+        .delimited_by(just('[').ignored(), just(']').ignored());
+
+    let end = take_until(just(')').ignored().rewind())
+        .map_with_span(|(s, _), r| (s.into_iter().collect(), r))
+        .delimited_by(just('(').ignored(), just(')').ignored());
+
+    start.then(end).map(|(_, s)| s)
+}
+
+/// Searches for all markdown link elements in a markdown stream and returns all target spans. Markdown images are
+/// parsed first and skipped, so the `[...](...)` of an image `![...](...)` is not taken for a link.
+fn find_links_in_markdown() -> impl Parser<char, Vec<(String, Range<usize>)>, Error = Simple<char>>
+{
+    let image = find_path_in_markdown_image().to(None);
+    let link = find_link_in_markdown().map(Some);
+
+    take_until(image.or(link))
+        .map(|(_, s)| s)
+        .repeated()
+        .map(|links| links.into_iter().flatten().collect())
+}
+
 /// Searches for all HTML or markdown image elements in a markdown stream and returns all possible path spans.
 fn find_paths_in_markdown() -> impl Parser<char, Vec<(String, Range<usize>)>, Error = Simple<char>>
 {
-    take_until(find_path_in_markdown_image().or(find_paths_in_html()))
+    let image = find_path_in_markdown_image().map(|s| vec![s]);
+
+    take_until(image.or(find_paths_in_html()))
         .map(|(_, s)| s)
         .repeated()
+        .flatten()
 }
 
 /// All possible errors that can occur when applying a `wrap-image[...]` tag of a cell.
@@ -77,8 +149,16 @@ pub enum WrapError {
     /// An error that occurs when the tag is not set up properly. (e.g. no closing `{}`)
     SplitError(Vec<Simple<char>>),
     /// An error that occurs when the `usize` in an `{}` is not less then the amount of
-    /// possible images in a cell.
-    OutOfIndex(usize, usize),
+    /// possible images in a cell. Contains the placeholder, its index and the amount of images.
+    OutOfIndex(String, usize, usize),
+    /// An error that occurs when a named placeholder like `{first}` is used, but the cell has
+    /// no image. Contains the name of the placeholder.
+    NoImage(String),
+    /// An error that occurs when the repeat placeholder `{*}` is mixed with other placeholders.
+    MixedRepeat,
+    /// An error that occurs in the strict mode, when the number of sequential placeholders `{}`
+    /// differs from the number of images. Contains the number of placeholders and images.
+    CountMismatch(usize, usize),
 }
 impl Display for WrapError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -87,7 +167,26 @@ impl Display for WrapError {
             WrapError::SplitError(err) => {
                 write!(f, "Unable to split the content properly. {:?}", err)
             }
-            WrapError::OutOfIndex(len, i) => write!(f, "Out of index. Len: {} Index: {}", len, i),
+            WrapError::OutOfIndex(placeholder, i, len) => write!(
+                f,
+                "The placeholder '{{{placeholder}}}' refers to the image at index {i}, but only {len} images were found."
+            ),
+            WrapError::NoImage(name) => {
+                write!(
+                    f,
+                    "The placeholder '{{{name}}}' is used, but there is no image."
+                )
+            }
+            WrapError::MixedRepeat => {
+                write!(
+                    f,
+                    "The placeholder '{{*}}' can not be mixed with other placeholders."
+                )
+            }
+            WrapError::CountMismatch(placeholders, images) => write!(
+                f,
+                "The wrap has {placeholders} placeholders '{{}}', but {images} images were found."
+            ),
             WrapError::MarkdownError(err) => {
                 write!(f, "Unable to the markdown properly. {:?}", err)
             }
@@ -100,10 +199,17 @@ impl Error for WrapError {}
 /// to retrieve the paths to the images. These paths are then wrapped in the string provided by the content
 /// of the tag.
 ///
+/// If only sequential placeholders `{}` are used and their number differs from the number of images, a
+/// warning is logged, or an error is returned if `strict` is set.
+///
 /// # Errors
 /// An error will be returned if the number inside a `{}` is defined incorrectly or is too large. Additionally,
 /// an error may occur during parsing of the tag or markdown.
-pub fn wrap_image(markdown: &str, wrap: &str) -> std::result::Result<String, WrapError> {
+pub fn wrap_image(
+    markdown: &str,
+    wrap: &str,
+    strict: bool,
+) -> std::result::Result<String, WrapError> {
     let paths = match find_paths_in_markdown().parse(markdown) {
         Ok(ok) => ok.into_iter().map(|s| s.0).collect::<Vec<_>>(),
         Err(err) => return Err(WrapError::MarkdownError(err)),
@@ -126,21 +232,51 @@ pub fn wrap_image(markdown: &str, wrap: &str) -> std::result::Result<String, Wra
         Err(err) => return Err(WrapError::SplitError(err)),
     };
 
+    if splits.iter().any(|(_, right)| right == "*") {
+        if splits.iter().any(|(_, right)| right != "*") {
+            return Err(WrapError::MixedRepeat);
+        }
+        let text = paths
+            .iter()
+            .map(|path| {
+                let start = splits
+                    .iter()
+                    .map(|(left, _)| format!("{left}{path}"))
+                    .collect::<String>();
+                format!("{start}{end}")
+            })
+            .collect();
+        return Ok(text);
+    }
+
+    if !splits.is_empty()
+        && splits.iter().all(|(_, right)| right.is_empty())
+        && splits.len() != paths.len()
+    {
+        let mismatch = WrapError::CountMismatch(splits.len(), paths.len());
+        if strict {
+            return Err(mismatch);
+        }
+        warn!("{mismatch}");
+    }
+
     let start = splits
         .into_iter()
         .enumerate()
         .map(|(i, (left, right))| {
-            let i = if right.is_empty() {
-                i
-            } else {
-                match right.parse::<usize>() {
+            let i = match right.as_str() {
+                "" => i,
+                "first" | "last" if paths.is_empty() => return Err(WrapError::NoImage(right)),
+                "first" => 0,
+                "last" => paths.len() - 1,
+                _ => match right.parse::<usize>() {
                     Ok(ok) => ok,
                     Err(err) => return Err(WrapError::ParseIntError(err)),
-                }
+                },
             };
 
             if i >= paths.len() {
-                return Err(WrapError::OutOfIndex(i, paths.len()));
+                return Err(WrapError::OutOfIndex(right, i, paths.len()));
             }
 
             Ok(format!("{}{}", left, paths[i]))
@@ -151,62 +287,329 @@ pub fn wrap_image(markdown: &str, wrap: &str) -> std::result::Result<String, Wra
     Ok(text)
 }
 
-/// Since the paths in a notebook are relative, this function replaces the paths to point to the images relative to the `output_path`.
+/// Returns `true` if the `path` is relative. Absolute paths, including
+/// Windows paths like `C:\` or `\\server`, web addresses, embedded `data:`
+/// images and `attachment:` references are not.
+fn is_relative_path(path: &str) -> bool {
+    let mut chars = path.chars();
+    let has_drive = matches!(
+        (chars.next(), chars.next(), chars.next()),
+        (Some(drive), Some(':'), Some('\\' | '/')) if drive.is_ascii_alphabetic()
+    );
+    !(has_drive
+        || path.starts_with('/')
+        || path.starts_with('\\')
+        || path.starts_with("http://")
+        || path.starts_with("https://")
+        || path.starts_with("data:")
+        || path.starts_with("attachment:"))
+}
+
+/// Returns the local path of the `path`, which is rewritten: a relative path
+/// itself, or the path of a `file://` url, which may be absolute. `None` is
+/// returned for the other paths of [`is_relative_path`] and for `file://` urls
+/// of another host, like the Windows UNC path `file://server/share/plot.png`.
+fn local_path(path: &str) -> Option<&str> {
+    let Some(url) = path.strip_prefix("file://") else {
+        return is_relative_path(path).then_some(path);
+    };
+    let url = url
+        .strip_prefix("localhost")
+        .filter(|url| url.starts_with('/'))
+        .unwrap_or(url);
+    if url.starts_with("./") || url.starts_with("../") {
+        return Some(url);
+    }
+
+    let path = url.strip_prefix('/')?;
+    if !is_relative_path(path) && path.chars().nth(1) == Some(':') {
+        // A Windows drive like `file:///C:/plot.png`.
+        return Some(path);
+    }
+    Some(url)
+}
+
+/// Finds all relative image paths and the paths of local `file://` urls in the
+/// markdown and returns them with the span of the original path. Windows path
+/// separators `\` are replaced by `/`.
+pub fn find_relative_paths(markdown: &str) -> Vec<(String, Range<usize>)> {
+    let paths = find_paths_in_markdown().parse::<_, &str>(markdown).unwrap();
+
+    paths
+        .into_iter()
+        .filter_map(|(path, range)| Some((local_path(&path)?.replace('\\', "/"), range)))
+        .collect()
+}
+
+/// Finds all relative link targets in the markdown and returns them with their
+/// span, like [`find_relative_paths`]. Links to an anchor and `mailto:` links
+/// are skipped as well.
+fn find_relative_links(markdown: &str) -> Vec<(String, Range<usize>)> {
+    let links = find_links_in_markdown().parse::<_, &str>(markdown).unwrap();
+
+    links
+        .into_iter()
+        .filter(|(link, _)| !link.starts_with('#') && !link.starts_with("mailto:"))
+        .filter_map(|(link, range)| Some((local_path(&link)?.replace('\\', "/"), range)))
+        .collect()
+}
+
+/// Finds all relative image paths and link targets in the markdown and returns
+/// them with their span, sorted by their position.
+fn find_relative_paths_and_links(markdown: &str) -> Vec<(String, Range<usize>)> {
+    let mut paths = find_relative_paths(markdown);
+    paths.extend(find_relative_links(markdown));
+    paths.sort_by_key(|(_, range)| range.start);
+
+    paths
+}
+
+/// Replaces the spans of the markdown with the given paths. The spans must be
+/// sorted in ascending order and must not overlap.
+fn replace_spans(mut markdown: String, replacements: Vec<(String, Range<usize>)>) -> String {
+    for (path, range) in replacements.into_iter().rev() {
+        let left = &markdown.chars().take(range.start()).collect::<String>();
+        let right = &markdown.chars().skip(range.end()).collect::<String>();
+        markdown = format!("{left}{path}{right}");
+    }
+
+    markdown
+}
+
+/// Since the paths in a notebook are relative, this function replaces the paths to point to the images and linked files relative
+/// to the `output_path`. The paths are resolved against the `relative_to` directory if given, otherwise against the parent of the `notebook_path`.
+/// If an `assets` directory is given, the images are copied into it with [`copy_asset`] and the paths point to the copies. Images
+/// that could not be copied keep pointing to the originals. If a `base_url` is given instead, the relative paths are joined onto it,
+/// e.g. `./images/plot.png` becomes `https://cdn.example.com/week1/images/plot.png`; absolute paths still point to the files.
 /// This function will return `None`, if neither the `output_path` nor the `notebook_path` have a parent directory. Note that
 /// this scenario should not occur, as both paths are file paths.
 pub fn replace_paths(
     output_path: &Path,
     notebook_path: &Path,
-    mut markdown: String,
+    relative_to: Option<&Path>,
+    assets: Option<&Path>,
+    base_url: Option<&str>,
+    markdown: String,
 ) -> Option<String> {
-    let paths = find_paths_in_markdown()
-        .parse::<_, &str>(&markdown)
-        .unwrap();
+    let base_path = match relative_to {
+        Some(relative_to) => relative_to,
+        None => notebook_path.parent()?,
+    };
+
+    let mut replacements = vec![];
+    for (path, range) in find_relative_paths_and_links(&markdown) {
+        let (file, suffix) = split_suffix(&path);
+        let copied = assets.and_then(|assets| {
+            copy_asset(output_path, assets, &base_path.join(decode_path(file)))
+                .map_err(|err| warn!("Unable to copy the image {path:?} to the assets. {err}"))
+                .ok()
+        });
+        let new_path = match (copied, base_url) {
+            (Some(copied), _) => copied,
+            (None, Some(base_url)) if !Path::new(file).is_absolute() => {
+                let file = file.trim_start_matches("./");
+                let url = format!("{}/{file}", base_url.trim_end_matches('/'));
+                replacements.push((format!("{url}{suffix}"), range));
+                continue;
+            }
+            (None, _) => generate_new_path(output_path, base_path, Path::new(file))?,
+        };
+        if let Some(new_path) = new_path.to_str() {
+            let new_path = encode_path(&new_path.replace('\\', "/"));
+            replacements.push((format!("{new_path}{suffix}"), range));
+        }
+    }
+
+    Some(replace_spans(markdown, replacements))
+}
+
+/// Splits the `path` into the path of the file and its query or fragment
+/// suffix, e.g. `?v=3` or `#layer2`, which is empty if there is none.
+pub fn split_suffix(path: &str) -> (&str, &str) {
+    path.split_at(path.find(['?', '#']).unwrap_or(path.len()))
+}
+
+/// Percent-encodes the chars of the `path` that are not safe in an url, like
+/// spaces and parentheses. Path separators `/`, queries, anchors and already
+/// encoded sequences like `%20` are kept.
+fn encode_path(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut encoded = String::with_capacity(path.len());
+    for (i, &byte) in bytes.iter().enumerate() {
+        let is_encoded = byte == b'%'
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_hexdigit)
+            && bytes.get(i + 2).is_some_and(u8::is_ascii_hexdigit);
+        if is_encoded || byte.is_ascii_alphanumeric() || b"-._~/#?=&+,;:@!$*".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{byte:02X}"));
+        }
+    }
+
+    encoded
+}
+
+/// Decodes the percent-encoded sequences of the `path`, so it can be used to
+/// access the file system. Invalid sequences are kept as they are.
+pub fn decode_path(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Returns the mime type of the image at the `path` by its extension, or
+/// `None` if the extension is not a known image type.
+fn image_mime_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    let mime = match extension.as_str() {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "svg" => "image/svg+xml",
+        "webp" => "image/webp",
+        "bmp" => "image/bmp",
+        _ => return None,
+    };
+    Some(mime)
+}
 
-    for (path, range) in paths.into_iter().rev() {
-        if path.starts_with('/') || path.starts_with("http://") || path.starts_with("https://") {
+/// Replaces the relative image paths in the markdown with `data:` urls that
+/// contain the base64 encoded images, so the presentation is self-contained.
+/// The paths are resolved like in [`replace_paths`]. Images that could not be
+/// read or have an unknown type keep their path.
+/// This function will return `None`, if the `notebook_path` has no parent directory.
+pub fn embed_images(
+    notebook_path: &Path,
+    relative_to: Option<&Path>,
+    markdown: String,
+) -> Option<String> {
+    let base_path = match relative_to {
+        Some(relative_to) => relative_to,
+        None => notebook_path.parent()?,
+    };
+
+    let mut replacements = vec![];
+    for (path, range) in find_relative_paths(&markdown) {
+        let image_path = base_path.join(decode_path(split_suffix(&path).0));
+        let Some(mime) = image_mime_type(&image_path) else {
+            warn!("Unable to embed the image {path:?}, because its type is unknown.");
             continue;
+        };
+        match fs::read(&image_path) {
+            Ok(image) => {
+                let data = base64::engine::general_purpose::STANDARD.encode(image);
+                replacements.push((format!("data:{mime};base64,{data}"), range));
+            }
+            Err(err) => warn!("Unable to embed the image {path:?}. {err}"),
         }
-        if let Some(new_path) =
-            generate_new_path(output_path, notebook_path, Path::new(&path))?.to_str()
-        {
-            let left = &markdown.chars().take(range.start()).collect::<String>();
-            let right = &markdown.chars().skip(range.end()).collect::<String>();
-            markdown = format!("{left}{new_path}{right}");
+    }
+
+    Some(replace_spans(markdown, replacements))
+}
+
+/// Removes the `.` and `..` components and the root of the `path` without
+/// accessing the file system, so it can be joined to another directory.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => normalized.push(name),
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => (),
         }
     }
 
-    Some(markdown)
+    normalized
+}
+
+/// Copies the image at the `source` path into the `assets` directory, which
+/// is relative to the directory of the `output_path`, and returns the path of
+/// the copy relative to the `output_path`. The directory structure of the
+/// `source` is kept, so the same image is only copied once. An existing copy
+/// is only replaced, if the image was modified afterwards.
+///
+/// # Errors
+///
+/// This function will return an error if the image could not be read or the
+/// copy could not be written.
+pub fn copy_asset(output_path: &Path, assets: &Path, source: &Path) -> io::Result<PathBuf> {
+    let asset_path = assets.join(normalize_path(source));
+    let destination = output_path
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(&asset_path);
+
+    let modified = fs::metadata(source)?.modified()?;
+    let is_up_to_date = fs::metadata(&destination)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|copied| copied >= modified);
+    if !is_up_to_date {
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(source, &destination)?;
+    }
+
+    Ok(asset_path)
+}
+
+/// Prefixes all relative image paths and link targets in the markdown with the
+/// given `prefix`. This is used to make the paths of an included file relative
+/// to the including file.
+pub fn prefix_paths(prefix: &Path, markdown: String) -> String {
+    let replacements = find_relative_paths_and_links(&markdown)
+        .into_iter()
+        .filter_map(|(path, range)| Some((prefix.join(path).to_str()?.to_string(), range)))
+        .collect();
+
+    replace_spans(markdown, replacements)
 }
 
 /// Since the paths in a notebook are relative, this function corrects the paths to point to the images relative to the `output_path`.
-/// This function will return `None`, if neither the `output_path` nor the `notebook_path` have a parent directory. Note that
-/// this scenario should not occur, as both paths are file paths.
-fn generate_new_path(
-    output_path: &Path,
-    notebook_path: &Path,
-    element_path: &Path,
-) -> Option<PathBuf> {
+/// The `base_path` is the directory the `element_path` is relative to. This function will return `None`, if the `output_path`
+/// has no parent directory. Note that this scenario should not occur, as it is a file path.
+fn generate_new_path(output_path: &Path, base_path: &Path, element_path: &Path) -> Option<PathBuf> {
     Some(
         output_path
             .parent()?
             .iter()
             .map(|_| OsStr::new(".."))
             .collect::<PathBuf>()
-            .join(notebook_path.parent()?)
+            .join(base_path)
             .join(element_path),
     )
 }
 
 #[cfg(test)]
 mod test {
-    use std::path::Path;
+    use std::{fs, path::Path};
 
     use chumsky::Parser;
 
     use super::{
-        duble_quote_string, find_path_in_markdown_image, find_paths_in_html,
-        find_paths_in_markdown, replace_paths, single_quote_string, wrap_image,
+        decode_path, duble_quote_string, embed_images, find_links_in_markdown,
+        find_path_in_markdown_image, find_paths_in_html, find_paths_in_markdown, normalize_path,
+        prefix_paths, replace_paths, single_quote_string, split_suffix, wrap_image, WrapError,
     };
 
     #[test]
@@ -236,7 +639,71 @@ mod test {
         let text = "<img src=\"./images/image.png\" width=\"60%\">\n";
         let parser = find_paths_in_html();
         let r = parser.parse(text);
-        assert_eq!(Ok(("./images/image.png".to_string(), 10..28)), r);
+        assert_eq!(Ok(vec![("./images/image.png".to_string(), 10..28)]), r);
+
+        let text = "<video poster='./poster.png' data-src=\"./no.mp4\" src=\"./clip.mp4\">";
+        let r = parser.parse(text);
+        assert_eq!(
+            Ok(vec![
+                ("./poster.png".to_string(), 15..27),
+                ("./clip.mp4".to_string(), 54..64)
+            ]),
+            r
+        );
+    }
+
+    #[test]
+    fn test_replace_srcset() {
+        let markdown =
+            "<img src=\"./a.png\" srcset=\"./a.png 1x, https://web.de/b.png 2x,./c.png 3x\">"
+                .to_string();
+        let output_path = Path::new("presentations/output.rmd");
+        let notebook_path = Path::new("notebooks/input.ipynb");
+        let markdown = replace_paths(output_path, notebook_path, None, None, None, markdown);
+
+        assert_eq!(markdown, Some("<img src=\"../notebooks/./a.png\" srcset=\"../notebooks/./a.png 1x, https://web.de/b.png 2x,../notebooks/./c.png 3x\">".to_string()));
+    }
+
+    #[test]
+    fn test_replace_file_url() {
+        let markdown = "![](file://./images/plot.png)\n![](file:///home/user/plot%201.png)\n![](file:///C:/plots/plot.png)\n[Data](file://localhost/data/data.csv)\n![](file://server/share/plot.png)"
+            .to_string();
+        let output_path = Path::new("presentations/output.rmd");
+        let notebook_path = Path::new("notebooks/input.ipynb");
+        let markdown =
+            replace_paths(output_path, notebook_path, None, None, None, markdown).unwrap();
+
+        let lines = markdown.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "![](../notebooks/./images/plot.png)");
+        assert_eq!(lines[1], "![](/home/user/plot%201.png)");
+        assert!(lines[2].ends_with("C:/plots/plot.png)"), "{}", lines[2]);
+        assert_eq!(lines[3], "[Data](/data/data.csv)");
+        assert_eq!(lines[4], "![](file://server/share/plot.png)");
+    }
+
+    #[test]
+    fn test_replace_picture() {
+        let markdown = "<picture>\n  <source media=\"(min-width: 800px)\" srcset=\"./wide.webp 1x, ./wide@2x.webp 2x\" type=\"image/webp\">\n  <source srcset=\"https://web.de/narrow.webp\">\n  <img src=\"./fallback.png\" alt=\"Plot\">\n</picture>"
+            .to_string();
+        let output_path = Path::new("presentations/output.rmd");
+        let notebook_path = Path::new("notebooks/input.ipynb");
+        let markdown = replace_paths(output_path, notebook_path, None, None, None, markdown);
+
+        assert_eq!(markdown, Some("<picture>\n  <source media=\"(min-width: 800px)\" srcset=\"../notebooks/./wide.webp 1x, ../notebooks/./wide@2x.webp 2x\" type=\"image/webp\">\n  <source srcset=\"https://web.de/narrow.webp\">\n  <img src=\"../notebooks/./fallback.png\" alt=\"Plot\">\n</picture>".to_string()));
+    }
+
+    #[test]
+    fn test_find_paths_in_video() {
+        let text = "<video controls>\n  <source src=\"./clip.webm\">\n  <source src=\"./clip.mp4\">\n</video>";
+        let parser = find_paths_in_markdown();
+        let r = parser.parse(text);
+        assert_eq!(
+            Ok(vec![
+                ("./clip.webm".to_string(), 32..43),
+                ("./clip.mp4".to_string(), 61..71)
+            ]),
+            r
+        );
     }
 
     #[test]
@@ -286,12 +753,26 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_find_links_in_markdown() {
+        let text = "[Handout](./handout.pdf) ![Image](./image.png)\nWow! [Link](./data.csv)";
+        let parser = find_links_in_markdown();
+        let r = parser.parse(text);
+        assert_eq!(
+            Ok(vec![
+                ("./handout.pdf".to_string(), 10..23),
+                ("./data.csv".to_string(), 59..69)
+            ]),
+            r
+        );
+    }
+
     #[test]
     fn test_wrap_image() {
         let wrap = "![Some Image]({})  \n![Some Image]({})";
         let markdown = "![](./images/image1.png)\nsome text\n![](./images/image2.png)";
 
-        let wrapped = wrap_image(markdown, wrap);
+        let wrapped = wrap_image(markdown, wrap, false);
         assert_eq!(
             Ok(
                 "![Some Image](./images/image1.png)  \n![Some Image](./images/image2.png)"
@@ -299,6 +780,78 @@ mod test {
             ),
             wrapped
         );
+
+        let wrap = "<img src=\"{last}\">\n![]({first})\n![]({0})";
+        let wrapped = wrap_image(markdown, wrap, false);
+        assert_eq!(
+            Ok("<img src=\"./images/image2.png\">\n![](./images/image1.png)\n![](./images/image1.png)".to_string()),
+            wrapped
+        );
+
+        let wrapped = wrap_image("some text", wrap, false);
+        assert_eq!(Err(WrapError::NoImage("last".to_string())), wrapped);
+    }
+
+    #[test]
+    fn test_wrap_image_out_of_index() {
+        let markdown = "![](./images/image1.png)\n![](./images/image2.png)";
+
+        let err = wrap_image(markdown, "![]({0})\n![]({5})", false).unwrap_err();
+        assert_eq!(err, WrapError::OutOfIndex("5".to_string(), 5, 2));
+        assert_eq!(
+            err.to_string(),
+            "The placeholder '{5}' refers to the image at index 5, but only 2 images were found."
+        );
+    }
+
+    #[test]
+    fn test_wrap_image_count_mismatch() {
+        let markdown = "![](./a.png)\n![](./b.png)\n![](./c.png)";
+
+        // More images than placeholders drops the last image.
+        let wrap = "![]({}) ![]({})";
+        assert_eq!(
+            wrap_image(markdown, wrap, false),
+            Ok("![](./a.png) ![](./b.png)".to_string())
+        );
+        let err = wrap_image(markdown, wrap, true).unwrap_err();
+        assert_eq!(err, WrapError::CountMismatch(2, 3));
+        assert_eq!(
+            err.to_string(),
+            "The wrap has 2 placeholders '{}', but 3 images were found."
+        );
+
+        // Fewer images than placeholders.
+        let wrap = "![]({}) ![]({}) ![]({}) ![]({})";
+        assert_eq!(
+            wrap_image(markdown, wrap, false),
+            Err(WrapError::OutOfIndex(String::new(), 3, 3))
+        );
+        assert_eq!(
+            wrap_image(markdown, wrap, true),
+            Err(WrapError::CountMismatch(4, 3))
+        );
+
+        // Indexed placeholders are not counted.
+        assert!(wrap_image(markdown, "![]({first})", true).is_ok());
+        assert!(wrap_image(markdown, "![]({}) ![]({}) ![]({})", true).is_ok());
+    }
+
+    #[test]
+    fn test_wrap_image_repeat() {
+        let wrap = "![plot]({*} \"{*}\")  \n";
+        let markdown = "![](./a.png)\n<img src=\"./b.png\">\ntext\n![](./c.png)";
+
+        let wrapped = wrap_image(markdown, wrap, false);
+        assert_eq!(
+            Ok("![plot](./a.png \"./a.png\")  \n![plot](./b.png \"./b.png\")  \n![plot](./c.png \"./c.png\")  \n".to_string()),
+            wrapped
+        );
+        assert_eq!(Ok(String::new()), wrap_image("text", wrap, false));
+        assert_eq!(
+            Err(WrapError::MixedRepeat),
+            wrap_image(markdown, "![]({*})\n![]({0})", false)
+        );
     }
 
     #[test]
@@ -310,24 +863,208 @@ mod test {
         let output_path = Path::new("presentations/output.rmd");
         let notebook_path = Path::new("notebooks/input.ipynb");
 
-        let markdown = replace_paths(output_path, notebook_path, markdown);
+        let markdown = replace_paths(output_path, notebook_path, None, None, None, markdown);
 
         assert_eq!(markdown, Some("# Header\n![](../notebooks/./images/image1.png)\n<src = \"../notebooks/./images/image2.png\">\n![](https://webimage/image.png)\nSome Text".to_string()));
     }
 
+    #[test]
+    fn test_replace_link() {
+        let markdown =
+            "![Image](./image.png) [Handout](./handout.pdf) [Web](https://web.de) [Slide](#slide)"
+                .to_string();
+        let output_path = Path::new("presentations/output.rmd");
+        let notebook_path = Path::new("notebooks/input.ipynb");
+        let markdown = replace_paths(output_path, notebook_path, None, None, None, markdown);
+
+        assert_eq!(markdown, Some("![Image](../notebooks/./image.png) [Handout](../notebooks/./handout.pdf) [Web](https://web.de) [Slide](#slide)".to_string()));
+    }
+
+    #[test]
+    fn test_replace_path_encoded() {
+        let markdown =
+            "![](./my figures/plot 1.png)\n<img src=\"./plot (1).png\">\n![](./plot%201.png)"
+                .to_string();
+        let output_path = Path::new("presentations/output.rmd");
+        let notebook_path = Path::new("notebooks/input.ipynb");
+        let markdown = replace_paths(output_path, notebook_path, None, None, None, markdown);
+
+        assert_eq!(markdown, Some("![](../notebooks/./my%20figures/plot%201.png)\n<img src=\"../notebooks/./plot%20%281%29.png\">\n![](../notebooks/./plot%201.png)".to_string()));
+        assert_eq!(decode_path("./plot%20%281%29.png%2"), "./plot (1).png%2");
+    }
+
+    #[test]
+    fn test_replace_windows_path() {
+        let markdown =
+            "![](.\\images\\plot.png)\n![](C:\\images\\plot.png)\n![](D:/plot.png)".to_string();
+        let output_path = Path::new("presentations/output.rmd");
+        let notebook_path = Path::new("notebooks/input.ipynb");
+        let markdown = replace_paths(output_path, notebook_path, None, None, None, markdown);
+
+        assert_eq!(
+            markdown,
+            Some(
+                "![](../notebooks/./images/plot.png)\n![](C:\\images\\plot.png)\n![](D:/plot.png)"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_replace_path_suffix() {
+        let markdown =
+            "![](./diagram.svg#layer2)\n<img src=\"./data.png?v=3\">\n[Plot](./my plot.png?v=1#top)"
+                .to_string();
+        let output_path = Path::new("presentations/output.rmd");
+        let notebook_path = Path::new("notebooks/input.ipynb");
+        let markdown = replace_paths(output_path, notebook_path, None, None, None, markdown);
+
+        assert_eq!(markdown, Some("![](../notebooks/./diagram.svg#layer2)\n<img src=\"../notebooks/./data.png?v=3\">\n[Plot](../notebooks/./my%20plot.png?v=1#top)".to_string()));
+        assert_eq!(split_suffix("./data.png"), ("./data.png", ""));
+    }
+
     #[test]
     fn test_replace_path2() {
         let markdown =
         "Here Is a cell with images.  \n<img src = \"./../images/image1.png\">  The text gets ignored.  \n![Image1](./../images/image2.png)  \n".to_string();
 
         let wrap = "wrap-image[<img src=\"{}\">\n\n![Image1]({})]";
-        let markdown = wrap_image(&markdown, wrap).unwrap();
+        let markdown = wrap_image(&markdown, wrap, false).unwrap();
 
         let output_path = Path::new("presentations/output.rmd");
         let notebook_path = Path::new("notebooks/input.ipynb");
 
-        let markdown = replace_paths(output_path, notebook_path, markdown);
+        let markdown = replace_paths(output_path, notebook_path, None, None, None, markdown);
 
         assert_eq!(markdown, Some("wrap-image[<img src=\"../notebooks/./../images/image1.png\">\n\n![Image1](../notebooks/./../images/image2.png)]".to_string()));
     }
+
+    #[test]
+    fn test_replace_path_relative_to() {
+        let markdown = "![](./images/image1.png)\n![](https://webimage/image.png)".to_string();
+
+        let output_path = Path::new("presentations/output.rmd");
+        let notebook_path = Path::new("notebooks/input.ipynb");
+
+        let default = replace_paths(
+            output_path,
+            notebook_path,
+            None,
+            None,
+            None,
+            markdown.clone(),
+        );
+        assert_eq!(
+            default,
+            Some(
+                "![](../notebooks/./images/image1.png)\n![](https://webimage/image.png)"
+                    .to_string()
+            )
+        );
+
+        let relative_to = replace_paths(
+            output_path,
+            notebook_path,
+            Some(Path::new("assets")),
+            None,
+            None,
+            markdown,
+        );
+        assert_eq!(
+            relative_to,
+            Some("![](../assets/./images/image1.png)\n![](https://webimage/image.png)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_replace_paths_base_url() {
+        let output_path = Path::new("presentations/output.rmd");
+        let notebook_path = Path::new("notebooks/week1/input.ipynb");
+        let markdown = "![](./images/plot.png)\n<img src=\"../shared/logo.png\">\n![](https://webimage/image.png)\n![](/abs/plot.png)".to_string();
+
+        for base_url in [
+            "https://cdn.example.com/week1",
+            "https://cdn.example.com/week1/",
+        ] {
+            let markdown = replace_paths(
+                output_path,
+                notebook_path,
+                None,
+                None,
+                Some(base_url),
+                markdown.clone(),
+            );
+            assert_eq!(
+                markdown,
+                Some("![](https://cdn.example.com/week1/images/plot.png)\n<img src=\"https://cdn.example.com/week1/../shared/logo.png\">\n![](https://webimage/image.png)\n![](/abs/plot.png)".to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn test_copy_assets() {
+        let dir = tempfile::tempdir().unwrap();
+        let notebook_path = dir.path().join("lectures/week1/notebook.ipynb");
+        let image_path = dir.path().join("lectures/week1/images/plot.png");
+        fs::create_dir_all(image_path.parent().unwrap()).unwrap();
+        fs::write(&image_path, "png").unwrap();
+        let output_path = dir.path().join("deck/output.rmd");
+        let markdown =
+            "![](./images/plot.png)\n<img src=\"images/plot.png\">\n![](./missing.png)".to_string();
+
+        let markdown = replace_paths(
+            &output_path,
+            &notebook_path,
+            None,
+            Some(Path::new("assets")),
+            None,
+            markdown,
+        )
+        .unwrap();
+
+        let asset_path = Path::new("assets")
+            .join(normalize_path(dir.path()))
+            .join("lectures/week1/images/plot.png");
+        let asset = asset_path.to_str().unwrap();
+        assert!(markdown.starts_with(&format!("![]({asset})\n<img src=\"{asset}\">\n![](")));
+        assert!(markdown.ends_with("./missing.png)"));
+        assert_eq!(
+            fs::read_to_string(dir.path().join("deck").join(&asset_path)).unwrap(),
+            "png"
+        );
+    }
+
+    #[test]
+    fn test_embed_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let notebook_path = dir.path().join("notebook.ipynb");
+        fs::write(dir.path().join("image.png"), [0x89, b'P', b'N', b'G']).unwrap();
+        let markdown =
+            "![](./image.png)\n<img src=\"missing.png\">\n![](https://webimage/image.png)"
+                .to_string();
+
+        let markdown = embed_images(&notebook_path, None, markdown);
+
+        assert_eq!(
+            markdown,
+            Some(
+                "![](data:image/png;base64,iVBORw==)\n<img src=\"missing.png\">\n![](https://webimage/image.png)"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_prefix_paths() {
+        let markdown =
+            "![](./image1.png)\n<img src=\"image2.png\">\n![](https://webimage/image.png)\n![](data:image/png;base64,iVBORw0KGgo=)"
+                .to_string();
+
+        let markdown = prefix_paths(Path::new("shared"), markdown);
+
+        assert_eq!(
+            markdown,
+            "![](shared/./image1.png)\n<img src=\"shared/image2.png\">\n![](https://webimage/image.png)\n![](data:image/png;base64,iVBORw0KGgo=)"
+        );
+    }
 }
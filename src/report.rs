@@ -0,0 +1,144 @@
+//! Render errors for the command line, with an excerpt of the command comment
+//! that could not be parsed.
+
+use std::{
+    env,
+    error::Error,
+    fmt::Display,
+    io::{self, IsTerminal},
+    ops::Range,
+};
+
+use crate::commands::ParseError;
+
+/// The escape sequence that starts the highlight of the offending fragment.
+const HIGHLIGHT: &str = "\x1b[1;31m";
+
+/// The escape sequence that resets the highlight.
+const RESET: &str = "\x1b[0m";
+
+/// Indicates the commands of a command comment could not be parsed. Contains
+/// the joined command `stream` of the comment and the [`ParseError`].
+#[derive(Debug)]
+pub struct CommandError {
+    /// The commands of the comment, without `<!--!` and `-->`.
+    pub stream: String,
+    /// The error of the parser.
+    pub error: ParseError,
+}
+
+impl Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Unable to parse commands. '{}' {} ",
+            self.stream, self.error
+        )
+    }
+}
+
+impl Error for CommandError {}
+
+impl CommandError {
+    /// Returns the char range of the offending fragment in the stream. Errors
+    /// without an offset point to the command they name, or else to the
+    /// whole stream.
+    fn span(&self) -> Range<usize> {
+        let len = self.stream.chars().count();
+        if let Some(offset) = self.error.offset() {
+            let start = offset.min(len);
+            return start..len.max(start + 1);
+        }
+        let (ParseError::UnknownCommand(name) | ParseError::Content(name)) = &self.error else {
+            return 0..len;
+        };
+        match self
+            .stream
+            .to_ascii_lowercase()
+            .find(&name.to_ascii_lowercase())
+        {
+            Some(start) => {
+                let start = self.stream[..start].chars().count();
+                start..start + name.chars().count()
+            }
+            None => 0..len,
+        }
+    }
+
+    /// Renders the stream with the offending fragment underlined, optionally
+    /// highlighted with `color`.
+    pub fn excerpt(&self, color: bool) -> String {
+        let span = self.span();
+        let indent = " ".repeat(span.start);
+        let marker = "^".repeat(span.len());
+        if !color {
+            return format!("  | {}\n  | {indent}{marker}", self.stream);
+        }
+
+        let chars = self.stream.chars().collect::<Vec<_>>();
+        let slice = |range: Range<usize>| {
+            chars[range.start.min(chars.len())..range.end.min(chars.len())]
+                .iter()
+                .collect::<String>()
+        };
+        format!(
+            "  | {}{HIGHLIGHT}{}{RESET}{}\n  | {indent}{HIGHLIGHT}{marker}{RESET}",
+            slice(0..span.start),
+            slice(span.clone()),
+            slice(span.end..chars.len()),
+        )
+    }
+}
+
+/// Returns `true` if the errors written to stderr should be colored, which is
+/// the case for a terminal, unless `NO_COLOR` is set.
+pub fn use_color() -> bool {
+    env::var_os("NO_COLOR").is_none() && io::stderr().is_terminal()
+}
+
+/// Renders the error `err` with an excerpt of the failing command comment, if
+/// a [`CommandError`] is part of the error chain.
+pub fn render(err: &anyhow::Error, color: bool) -> String {
+    match err
+        .chain()
+        .find_map(|err| err.downcast_ref::<CommandError>())
+    {
+        Some(command_error) => format!("{err}\n{}", command_error.excerpt(color)),
+        None => err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::commands::{self, ParseError};
+
+    use super::{render, CommandError};
+
+    #[test]
+    fn test_render() {
+        let stream = "new; strat-add".to_string();
+        let error = commands::parse(&stream).unwrap_err();
+        let err = anyhow::Error::new(CommandError { stream, error });
+        let err = err.context("Line 2 to 3.");
+        assert_eq!(
+            render(&err, false),
+            "Line 2 to 3.\n  | new; strat-add\n  |      ^^^^^^^^^"
+        );
+        assert_eq!(
+            render(&err, true),
+            "Line 2 to 3.\n  | new; \x1b[1;31mstrat-add\x1b[0m\n  |      \x1b[1;31m^^^^^^^^^\x1b[0m"
+        );
+
+        let err = anyhow::Error::new(CommandError {
+            stream: "new add".to_string(),
+            error: ParseError::MissingComma("add".to_string(), 4),
+        });
+        assert_eq!(
+            render(&err, false),
+            format!("{err}\n  | new add\n  |     ^^^")
+        );
+
+        let err = anyhow::Error::msg("Other error.");
+        assert_eq!(render(&err, false), "Other error.");
+    }
+}
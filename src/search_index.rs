@@ -0,0 +1,126 @@
+//! Creates a search index for the generated presentation, which maps every
+//! search term to the slides it occurs in. The index is written as `json` and
+//! can be used by a client-side search widget.
+use anyhow::Result;
+use serde::Serialize;
+use std::{collections::BTreeMap, fs::File, path::PathBuf};
+
+use crate::format::split_into_slides;
+
+/// Words which are too common to be useful as a search term.
+const STOP_WORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "by", "for", "from", "in", "is", "it", "of", "on",
+    "or", "that", "the", "this", "to", "was", "with",
+];
+
+/// A reference to a single slide of the presentation.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SlideReference {
+    /// The number of the slide, starting at `1`.
+    pub slide: usize,
+    /// The anchor to jump to the slide in the rendered presentation.
+    pub anchor: String,
+}
+
+impl SlideReference {
+    /// Creates a reference to the slide with the given `number`.
+    fn new(number: usize) -> Self {
+        Self {
+            slide: number,
+            anchor: format!("#{number}"),
+        }
+    }
+}
+
+/// Splits the text of a slide into lowercase search terms. Fenced code blocks,
+/// inline code, the `class:` property and stop words are excluded.
+fn tokenize(slide: &str) -> Vec<String> {
+    let mut in_code_block = false;
+    let mut terms = vec![];
+
+    for line in slide.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block || trimmed.starts_with("class:") {
+            continue;
+        }
+
+        let text = line.split('`').step_by(2).collect::<Vec<_>>().join(" ");
+        terms.extend(
+            text.split(|c: char| !c.is_alphanumeric())
+                .map(str::to_lowercase)
+                .filter(|term| term.chars().count() > 1 && !STOP_WORDS.contains(&term.as_str())),
+        );
+    }
+
+    terms
+}
+
+/// Creates the search index for the given pages, whose slides are separated
+/// by the `separator`. Every term maps to the slides it occurs in, ordered by
+/// the slide number.
+pub fn create_search_index(
+    pages: &[String],
+    separator: &str,
+) -> BTreeMap<String, Vec<SlideReference>> {
+    let mut index: BTreeMap<String, Vec<SlideReference>> = BTreeMap::new();
+
+    for (i, slide) in split_into_slides(pages, separator).into_iter().enumerate() {
+        let number = i + 1;
+        for term in tokenize(slide) {
+            let references = index.entry(term).or_default();
+            if references.last().map(|reference| reference.slide) != Some(number) {
+                references.push(SlideReference::new(number));
+            }
+        }
+    }
+
+    index
+}
+
+/// Creates the search index for the given pages and writes it as `json` to
+/// the `index_path`.
+///
+/// # Errors
+///
+/// This function will return an error if the file could not be created or
+/// the index could not be written to it.
+pub fn write_search_index(index_path: PathBuf, pages: &[String], separator: &str) -> Result<()> {
+    let file = File::create(index_path)?;
+    serde_json::to_writer_pretty(file, &create_search_index(pages, separator))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::format::PAGE_SEPARATOR;
+
+    use super::{create_search_index, tokenize, SlideReference};
+
+    #[test]
+    fn test_tokenize() {
+        let slide =
+            "class: center\n\n# The Headline\nSome `code` text.\n```python\nprint('hidden')\n```\n";
+        assert_eq!(tokenize(slide), vec!["headline", "some", "text"]);
+    }
+
+    #[test]
+    fn test_create_search_index() {
+        let pages = vec![
+            "title: \"Test\"".to_string(),
+            String::new(),
+            "# Fire Dynamics\nSome text.\n---\n\n# Combustion\nMore fire.".to_string(),
+        ];
+
+        let index = create_search_index(&pages, PAGE_SEPARATOR);
+        assert_eq!(index.get("combustion"), Some(&vec![SlideReference::new(3)]));
+        assert_eq!(
+            index.get("fire"),
+            Some(&vec![SlideReference::new(2), SlideReference::new(3)])
+        );
+        assert_eq!(index.get("the"), None);
+    }
+}
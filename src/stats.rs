@@ -0,0 +1,121 @@
+//! Counts the slides and the words per slide of the generated presentation,
+//! which helps to estimate the timing of a talk and to find dense slides.
+use std::fmt::Write;
+
+use crate::format::{split_into_slides, split_properties};
+
+/// The number of words above which a slide is flagged as dense, if no other
+/// threshold is given.
+pub const DEFAULT_DENSE_WORDS: usize = 150;
+
+/// Counts the words shown on the `slide`. Fenced code blocks, comments like
+/// the command comments, the properties and the speaker notes after `???`
+/// are not counted.
+pub fn count_words(slide: &str) -> usize {
+    let (_, content) = split_properties(slide);
+    let mut in_code_block = false;
+    let mut in_comment = false;
+    let mut words = 0;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if !in_comment && trimmed.starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        if !in_comment && trimmed == "???" {
+            break;
+        }
+
+        let mut rest = line;
+        while !rest.is_empty() {
+            if in_comment {
+                match rest.split_once("-->") {
+                    Some((_, after)) => {
+                        in_comment = false;
+                        rest = after;
+                    }
+                    None => rest = "",
+                }
+            } else {
+                let (text, after) = match rest.split_once("<!--") {
+                    Some((text, after)) => {
+                        in_comment = true;
+                        (text, after)
+                    }
+                    None => (rest, ""),
+                };
+                words += text
+                    .split_whitespace()
+                    .filter(|word| word.chars().any(char::is_alphanumeric))
+                    .count();
+                rest = after;
+            }
+        }
+    }
+
+    words
+}
+
+/// Creates a report with the number of slides, the words of every slide and
+/// the total number of words. The slides of the `pages` are separated by the
+/// `separator` and slides with more than `dense_words` words are flagged as
+/// dense.
+pub fn report(pages: &[String], dense_words: usize, separator: &str) -> String {
+    let words = split_into_slides(pages, separator)
+        .into_iter()
+        .map(count_words)
+        .collect::<Vec<_>>();
+
+    let mut report = format!("Slides: {}\n", words.len());
+    for (i, count) in words.iter().enumerate() {
+        let _ = write!(report, "{:>5} {count:>6} words", i + 1);
+        if *count > dense_words {
+            let _ = write!(report, " (dense, more than {dense_words} words)");
+        }
+        report.push('\n');
+    }
+    let _ = write!(report, "Words: {}", words.iter().sum::<usize>());
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use crate::format::PAGE_SEPARATOR;
+
+    use super::{count_words, report};
+
+    #[test]
+    fn test_count_words() {
+        assert_eq!(
+            count_words("class: center\n\n# The Headline\nSome text."),
+            4
+        );
+        assert_eq!(
+            count_words("# Code\n```python\nprint('hidden words')\n```\nAfter the code.\n"),
+            4
+        );
+        assert_eq!(
+            count_words("<!--! new; -->\nOne <!-- kept\ncomment --> two\n- three\n"),
+            3
+        );
+        assert_eq!(count_words("Visible text\n???\nThe speaker notes."), 2);
+        assert_eq!(count_words(""), 0);
+    }
+
+    #[test]
+    fn test_report() {
+        let pages = vec![
+            "# First slide\n---\n\nOne two three four".to_string(),
+            String::new(),
+            "```\nno words\n```\n".to_string(),
+        ];
+        assert_eq!(
+            report(&pages, 3, PAGE_SEPARATOR),
+            "Slides: 3\n    1      2 words\n    2      4 words (dense, more than 3 words)\n    3      0 words\nWords: 6"
+        );
+    }
+}
@@ -0,0 +1,101 @@
+//! Watches the input paths of the presentation and rebuilds it on every
+//! change, until the program is interrupted.
+use anyhow::Result;
+use log::{error, info};
+use notify::{Event, RecursiveMode, Watcher};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Duration,
+};
+
+/// The time to wait for further events, before the presentation is rebuild.
+/// Saving a notebook usually results in multiple events.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Returns `true` if the `event` changed a file that is not `ignored`, e.g.
+/// an output of the presentation.
+fn is_relevant(event: &Event, ignored: &[PathBuf]) -> bool {
+    !event.kind.is_access()
+        && event
+            .paths
+            .iter()
+            .any(|path| !ignored.iter().any(|ignored| path.starts_with(ignored)))
+}
+
+/// Watches the `inputs` and calls `rebuild` after every change. Directories
+/// are watched recursively, so new notebooks are picked up. For files the
+/// parent directory is watched, which includes files included next to them.
+/// Changes of the `ignored` paths do not trigger a rebuild. An error of
+/// `rebuild` is logged and the watching continues.
+///
+/// # Errors
+///
+/// This function will return an error if an input could not be watched.
+pub fn watch(
+    inputs: &[String],
+    ignored: &[PathBuf],
+    mut rebuild: impl FnMut() -> Result<()>,
+) -> Result<()> {
+    let ignored = ignored
+        .iter()
+        .filter_map(|path| fs::canonicalize(path).ok())
+        .collect::<Vec<_>>();
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    for input in inputs {
+        let path = Path::new(input);
+        if path.is_dir() {
+            watcher.watch(path, RecursiveMode::Recursive)?;
+        } else {
+            let parent = match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            watcher.watch(parent, RecursiveMode::NonRecursive)?;
+        }
+    }
+
+    info!("Watching for changes. Press Ctrl+C to stop.");
+    while let Ok(event) = receiver.recv() {
+        match event {
+            Ok(event) if is_relevant(&event, &ignored) => {
+                while receiver.recv_timeout(DEBOUNCE).is_ok() {}
+                info!(
+                    "Rebuild the presentation after a change of {:?}",
+                    event.paths
+                );
+                if let Err(err) = rebuild() {
+                    error!("{err}");
+                }
+            }
+            Ok(_) => (),
+            Err(err) => error!("{err}"),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use notify::{event::ModifyKind, Event, EventKind};
+    use std::path::PathBuf;
+
+    use super::is_relevant;
+
+    #[test]
+    fn test_is_relevant() {
+        let ignored = vec![PathBuf::from("/presentations/output.rmd")];
+        let event = Event::new(EventKind::Modify(ModifyKind::Any));
+
+        let output = event
+            .clone()
+            .add_path(PathBuf::from("/presentations/output.rmd"));
+        assert!(!is_relevant(&output, &ignored));
+        let notebook = event.add_path(PathBuf::from("/notebooks/book.ipynb"));
+        assert!(is_relevant(&notebook, &ignored));
+    }
+}
@@ -0,0 +1,19 @@
+//! Runs the program with a single notebook and without an output, which
+//! writes the presentation next to the notebook.
+use std::process::Command;
+
+#[test]
+fn test_default_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let notebook = dir.path().join("notebook.ipynb");
+    std::fs::copy("tests/notebooks/multiple_books/01_book.ipynb", &notebook).unwrap();
+
+    let status = Command::new(env!("CARGO_BIN_EXE_presentation"))
+        .arg(notebook.to_str().unwrap())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let presentation = std::fs::read_to_string(dir.path().join("notebook.rmd")).unwrap();
+    assert!(presentation.contains("This is the first page"));
+}
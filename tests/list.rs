@@ -0,0 +1,50 @@
+//! Lists the input paths of a directory and checks that the presentation
+//! combines the notebooks in the listed order.
+use std::process::Command;
+
+#[test]
+fn test_list() {
+    let dir = tempfile::tempdir().unwrap();
+    let notebooks = dir.path().join("notebooks");
+    std::fs::create_dir_all(notebooks.join("week2")).unwrap();
+    for name in ["intro", "week2/plumes", "combustion"] {
+        std::fs::write(
+            notebooks.join(format!("{name}.ipynb")),
+            format!(
+                r#"{{"cells": [{{"cell_type": "markdown", "metadata": {{}}, "source": ["<!--! new; inject[# Page {name}] -->"]}}]}}"#
+            ),
+        )
+        .unwrap();
+    }
+    let input = notebooks.to_str().unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_presentation"))
+        .args(["--list", input])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let listed = String::from_utf8(output.stdout).unwrap();
+    let listed = listed.lines().collect::<Vec<_>>();
+    assert_eq!(listed.len(), 3);
+    assert!(!dir.path().join("output.rmd").exists());
+
+    let output_path = dir.path().join("output.rmd");
+    let status = Command::new(env!("CARGO_BIN_EXE_presentation"))
+        .args(["-o", output_path.to_str().unwrap(), input])
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let presentation = std::fs::read_to_string(output_path).unwrap();
+    let positions = listed
+        .iter()
+        .map(|path| {
+            let name = path
+                .strip_prefix(input)
+                .unwrap()
+                .trim_start_matches('/')
+                .trim_end_matches(".ipynb");
+            presentation.find(&format!("# Page {name}")).unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+}
@@ -0,0 +1,40 @@
+//! Runs the program with a directory as output, which writes one presentation
+//! per notebook.
+use std::process::Command;
+
+#[test]
+fn test_output_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = format!("{}/", dir.path().join("slides").to_str().unwrap());
+
+    let status = Command::new(env!("CARGO_BIN_EXE_presentation"))
+        .args([
+            "-o",
+            &output,
+            "tests/notebooks/multiple_books/01_book.ipynb",
+            "tests/notebooks/multiple_books/02_book.ipynb",
+        ])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let slides = dir.path().join("slides");
+    let first = std::fs::read_to_string(slides.join("01_book.rmd")).unwrap();
+    let second = std::fs::read_to_string(slides.join("02_book.rmd")).unwrap();
+    assert!(first.contains("This is the first page"));
+    assert!(second.contains("This is the second page"));
+    assert_eq!(std::fs::read_dir(slides).unwrap().count(), 2);
+
+    // Every generated file is checked before it is overridden.
+    let output = Command::new(env!("CARGO_BIN_EXE_presentation"))
+        .args([
+            "-o",
+            &output,
+            "tests/notebooks/multiple_books/01_book.ipynb",
+        ])
+        .output()
+        .unwrap();
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("File already exist"));
+}
@@ -0,0 +1,33 @@
+//! Runs the program with a custom separator in front of a `YAML` header,
+//! whose `---` lines have to be kept.
+use std::process::Command;
+
+#[test]
+fn test_separator() {
+    let dir = tempfile::tempdir().unwrap();
+    let head = std::fs::read_to_string("tests/head_page.rmd").unwrap();
+    let fences = |text: &str| text.lines().filter(|line| *line == "---").count();
+
+    // The page numbers collect the pages before they are written, otherwise
+    // the presentation is streamed.
+    for (name, extra) in [("streamed.rmd", None), ("collected.rmd", Some("--page-numbers"))] {
+        let output = dir.path().join(name);
+        let status = Command::new(env!("CARGO_BIN_EXE_presentation"))
+            .args(["--separator", "***", "-o", output.to_str().unwrap()])
+            .args(extra)
+            .args([
+                "tests/head_page.rmd",
+                "tests/notebooks/multiple_books/01_book.ipynb",
+                "tests/notebooks/multiple_books/02_book.ipynb",
+            ])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let presentation = std::fs::read_to_string(output).unwrap();
+        assert!(presentation.starts_with(head.trim_end()), "{name}");
+        assert_eq!(fences(&presentation), fences(&head), "{name}");
+        assert!(presentation.contains("\n***\n"), "{name}");
+        assert!(presentation.contains("This is the second page"), "{name}");
+    }
+}
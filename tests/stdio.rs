@@ -0,0 +1,36 @@
+//! Runs the program with a notebook from `stdin` and the presentation written
+//! to `stdout`.
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+#[test]
+fn test_stdin_to_stdout() {
+    let notebook = r##"{
+        "cells": [
+            {
+                "cell_type": "markdown",
+                "metadata": {},
+                "source": ["<!--! new; start-add; -->\n", "# Piped page\n"]
+            }
+        ]
+    }"##;
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_presentation"))
+        .args(["-o", "-", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(notebook.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "# Piped page\n");
+}